@@ -0,0 +1,264 @@
+//! Human readable error reports
+//!
+//! Renders an error against the input it came from, rustc style:
+//! the offending line, an underline beneath the span, a short
+//! label and, where there is one, a hint.
+
+use std::{fmt::Write, io};
+
+use nash_parser::{lexer::LexError, parser::ParseError, span::Span};
+
+use crate::{interpret::InterpretError, runner};
+
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Option<Span>,
+    pub label: Option<String>,
+    pub hint: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>) -> Self {
+        Diagnostic {
+            message: message.into(),
+            span: None,
+            label: None,
+            hint: None,
+        }
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Renders the report. `origin` names where `source` came
+    /// from - a script path, or something like `<input>`.
+    pub fn render(&self, origin: &str, source: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "error: {}", self.message);
+
+        let Some(span) = self.span else {
+            if let Some(hint) = &self.hint {
+                let _ = writeln!(out, "  = hint: {hint}");
+            }
+            return out;
+        };
+
+        let line = source.lines().nth(span.line - 1).unwrap_or("");
+        let gutter = " ".repeat(span.line.to_string().len());
+
+        // Underline the span, stopping at the end of its first line
+        let line_start = source[..span.start.min(source.len())]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        let line_end = line_start + line.len();
+        let underlined = source
+            .get(span.start..span.end.min(line_end))
+            .map_or(0, |s| s.chars().count())
+            .max(1);
+
+        let _ = writeln!(out, "{gutter}--> {origin}:{}:{}", span.line, span.column);
+        let _ = writeln!(out, "{gutter} |");
+        let _ = writeln!(out, "{} | {}", span.line, line.replace('\t', " "));
+        let _ = write!(
+            out,
+            "{gutter} | {}{}",
+            " ".repeat(span.column - 1),
+            "^".repeat(underlined)
+        );
+        match &self.label {
+            Some(label) => {
+                let _ = writeln!(out, " {label}");
+            }
+            None => out.push('\n'),
+        }
+
+        if let Some(hint) = &self.hint {
+            let _ = writeln!(out, "{gutter} |");
+            let _ = writeln!(out, "{gutter} = hint: {hint}");
+        }
+
+        out
+    }
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(e: &LexError) -> Self {
+        let diagnostic = Diagnostic::new(e.to_string()).with_span(e.span());
+
+        match e {
+            LexError::UnterminatedLiteral { .. } => diagnostic
+                .with_label("unterminated literal started here")
+                .with_hint("close the literal with a matching quote"),
+            LexError::OpenEscape { .. } => diagnostic.with_label("nothing left to escape"),
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(e: &ParseError) -> Self {
+        if let ParseError::LexError(e) = e {
+            return e.into();
+        }
+
+        let diagnostic = Diagnostic::new(e.to_string()).with_span(e.span());
+
+        match e {
+            ParseError::ExpectedClosed { .. } => diagnostic
+                .with_label("this list is never closed")
+                .with_hint("add a matching ')'"),
+            ParseError::UnexpectedClosed { .. } => diagnostic.with_label("no matching '('"),
+            _ => diagnostic,
+        }
+    }
+}
+
+impl From<&InterpretError> for Diagnostic {
+    fn from(e: &InterpretError) -> Self {
+        let diagnostic = Diagnostic::new(e.to_string()).with_span(e.span());
+
+        match e {
+            InterpretError::TopLevelAtom { .. } => diagnostic
+                .with_label("not a list")
+                .with_hint("wrap commands in parens, like (ls)"),
+            InterpretError::InvalidPipelineStage { .. }
+            | InterpretError::ExpectedCommand { .. } => diagnostic.with_label("not a command"),
+            InterpretError::InvalidRedirect { form, .. } => diagnostic
+                .with_label("missing command or file")
+                .with_hint(format!("({form} (command) file)")),
+            InterpretError::InvalidDef { .. } => {
+                diagnostic.with_hint("(def name value), where name is not a list")
+            }
+            InterpretError::InvalidIf { .. } => {
+                diagnostic.with_hint("(if (condition) (then)) or (if (condition) (then) (else))")
+            }
+            InterpretError::EmptyPipeline { .. } | InterpretError::InvalidNot { .. } => diagnostic,
+        }
+    }
+}
+
+impl From<&io::Error> for Diagnostic {
+    fn from(e: &io::Error) -> Self {
+        let diagnostic = Diagnostic::new(e.to_string());
+
+        let Some(span) = runner::error_span(e) else {
+            return diagnostic;
+        };
+
+        // Programs that couldn't start, and anything else - like a
+        // redirect's file - that's missing or off limits
+        let diagnostic = diagnostic.with_span(span);
+        match (runner::spawn_status(e), e.kind()) {
+            (Some(127), _) => diagnostic.with_label("not found"),
+            (_, io::ErrorKind::NotFound) => diagnostic.with_label("no such file"),
+            (_, io::ErrorKind::PermissionDenied) => diagnostic.with_label("permission denied"),
+            _ => diagnostic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{self, RunnerError};
+
+    fn report(input: &str) -> String {
+        let err = runner::run(input).expect_err("expected an error");
+        err.report("<input>", input)
+    }
+
+    #[test]
+    fn test_unterminated_literal() {
+        assert_eq!(
+            report("(echo 'oops)"),
+            concat!(
+                "error: literal not terminated\n",
+                " --> <input>:1:7\n",
+                "  |\n",
+                "1 | (echo 'oops)\n",
+                "  |       ^^^^^^ unterminated literal started here\n",
+                "  |\n",
+                "  = hint: close the literal with a matching quote\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_unclosed_list_on_later_line() {
+        assert_eq!(
+            report("(ls)\n(echo\n  hi"),
+            concat!(
+                "error: expected ')'\n",
+                " --> <input>:2:1\n",
+                "  |\n",
+                "2 | (echo\n",
+                "  | ^ this list is never closed\n",
+                "  |\n",
+                "  = hint: add a matching ')'\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_command_not_found_points_at_program() {
+        assert_eq!(
+            report("(nonexistent_command_xyz -la)"),
+            concat!(
+                "error: command not found: nonexistent_command_xyz\n",
+                " --> <input>:1:2\n",
+                "  |\n",
+                "1 | (nonexistent_command_xyz -la)\n",
+                "  |  ^^^^^^^^^^^^^^^^^^^^^^^ not found\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_interpret_error_points_at_operand() {
+        let rendered = report("(pipe (ls) grep)");
+        assert!(rendered.starts_with("error: pipeline stages must be commands\n"));
+        assert!(rendered.contains("  |            ^^^^ not a command\n"));
+    }
+
+    #[test]
+    fn test_redirect_error_points_at_file() {
+        let rendered = report("(in (cat) /nonexistent/input.txt)");
+        assert!(rendered.contains("  |           ^^^^^^^^^^^^^^^^^^^^^^ no such file\n"));
+    }
+
+    #[test]
+    fn test_multiline_span_underlines_first_line_only() {
+        let diagnostic = Diagnostic::new("bad").with_span(Span::new(0, 9, 1, 1));
+        assert_eq!(
+            diagnostic.render("<input>", "(a\n b c)"),
+            "error: bad\n --> <input>:1:1\n  |\n1 | (a\n  | ^^\n"
+        );
+    }
+
+    #[test]
+    fn test_spanless_error_is_just_the_message() {
+        let err = RunnerError::Io(io::Error::other("broken pipe"));
+        assert_eq!(err.report("<input>", ""), "error: broken pipe\n");
+    }
+
+    #[test]
+    fn test_gutter_widens_with_line_number() {
+        let source = "\n".repeat(9) + "(ls))";
+        let rendered = report(&source);
+        assert!(
+            rendered.contains("  --> <input>:10:5\n   |\n10 | (ls))\n   |     ^ no matching '('\n")
+        );
+    }
+}
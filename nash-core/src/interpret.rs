@@ -1,15 +1,61 @@
-use nash_parser::parser::{Atom, Expression};
+use nash_parser::{
+    forms::Form,
+    parser::{Atom, Expression},
+    span::Span,
+};
 use thiserror::Error;
 
 use crate::runner::{
     Runnable,
+    conditional::Conditional,
     executable::{Clause, Executable},
+    logical::Logical,
+    pipeline::Pipeline,
+    redirect::{Redirect, RedirectKind},
 };
 
 #[derive(Error, Debug)]
 pub enum InterpretError {
     #[error("expression expected at top level")]
-    TopLevelAtom,
+    TopLevelAtom { span: Span },
+
+    #[error("pipe expects at least one stage")]
+    EmptyPipeline { span: Span },
+
+    #[error("pipeline stages must be commands")]
+    InvalidPipelineStage { span: Span },
+
+    #[error("{form} expects a command followed by a file")]
+    InvalidRedirect { form: String, span: Span },
+
+    #[error("def expects a name and a value")]
+    InvalidDef { span: Span },
+
+    #[error("{form} expects commands as operands")]
+    ExpectedCommand { form: String, span: Span },
+
+    #[error("not expects exactly one command")]
+    InvalidNot { span: Span },
+
+    #[error("if expects a condition, a branch, and optionally an else branch")]
+    InvalidIf { span: Span },
+}
+
+impl InterpretError {
+    /// The expression at fault - the whole form when its shape
+    /// is wrong, or the single offending operand
+    pub fn span(&self) -> Span {
+        match self {
+            InterpretError::TopLevelAtom { span }
+            | InterpretError::EmptyPipeline { span }
+            | InterpretError::InvalidPipelineStage { span }
+            | InterpretError::InvalidRedirect { span, .. }
+            | InterpretError::InvalidDef { span }
+            | InterpretError::ExpectedCommand { span, .. }
+            | InterpretError::InvalidNot { span }
+            | InterpretError::InvalidIf { span } => *span,
+        }
+    }
 }
 
 pub fn interpret(expressions: Vec<Expression>) -> Result<Vec<Runnable>, InterpretError> {
@@ -17,54 +63,228 @@ pub fn interpret(expressions: Vec<Expression>) -> Result<Vec<Runnable>, Interpre
 
     for e in expressions {
         match e {
-            Expression::List(v) => executables.push(interpret_list(v)?),
-            Expression::Atom(_) => Err(InterpretError::TopLevelAtom)?,
+            Expression::List(v, span) => executables.push(interpret_list(v, span)?),
+            Expression::Atom(_, span) => Err(InterpretError::TopLevelAtom { span })?,
         }
     }
 
     Ok(executables)
 }
 
-pub fn interpret_list(expressions: Vec<Expression>) -> Result<Runnable, InterpretError> {
-    let operator = expressions.first();
+pub fn interpret_list(
+    expressions: Vec<Expression>,
+    span: Span,
+) -> Result<Runnable, InterpretError> {
+    match operator(&expressions).and_then(Form::from_operator) {
+        Some(Form::Def) => interpret_def(expressions, span),
+        Some(Form::Pipe) => interpret_pipeline(expressions, span),
+        Some(Form::And) => Ok(Runnable::Logical {
+            logical: Logical::And(interpret_operands(expressions)?),
+        }),
+        Some(Form::Or) => Ok(Runnable::Logical {
+            logical: Logical::Or(interpret_operands(expressions)?),
+        }),
+        Some(Form::Not) => interpret_not(expressions, span),
+        Some(Form::If) => interpret_if(expressions, span),
+        Some(Form::In) => interpret_redirect(RedirectKind::In, expressions, span),
+        Some(Form::Out) => interpret_redirect(RedirectKind::Out, expressions, span),
+        Some(Form::Append) => interpret_redirect(RedirectKind::Append, expressions, span),
+        None => interpret_command(expressions, span),
+    }
+}
+
+/// Name of the form a list invokes. Quoted operators are never
+/// special forms, so `('pipe' ...)` runs a program called `pipe`.
+fn operator(expressions: &[Expression]) -> Option<&str> {
+    match expressions.first() {
+        Some(Expression::Atom(Atom::Symbol(s), _)) => Some(s),
+        _ => None,
+    }
+}
+
+/// Splits a special form into its operator and the rest
+fn split_form(expressions: Vec<Expression>) -> (String, std::vec::IntoIter<Expression>) {
+    let mut expressions = expressions.into_iter();
+    match expressions.next() {
+        Some(Expression::Atom(Atom::Symbol(form), _)) => (form, expressions),
+        _ => unreachable!("special forms are dispatched on their operator"),
+    }
+}
+
+/// `(def name value)` - the value may be an atom or a list,
+/// whose output is bound once the def runs.
+pub fn interpret_def(expressions: Vec<Expression>, span: Span) -> Result<Runnable, InterpretError> {
+    let [_, name, value] = <[Expression; 3]>::try_from(expressions)
+        .map_err(|_| InterpretError::InvalidDef { span })?;
+
+    let name = match name {
+        Expression::Atom(Atom::Symbol(s) | Atom::Literal(s), _) => s,
+        Expression::List(_, span) => return Err(InterpretError::InvalidDef { span }),
+    };
+
+    Ok(Runnable::Binding {
+        name,
+        value: Box::new(interpret_clause(value)?),
+    })
+}
+
+pub fn interpret_not(expressions: Vec<Expression>, span: Span) -> Result<Runnable, InterpretError> {
+    let [operand] = <[Runnable; 1]>::try_from(interpret_operands(expressions)?)
+        .map_err(|_| InterpretError::InvalidNot { span })?;
+
+    Ok(Runnable::Logical {
+        logical: Logical::Not(Box::new(operand)),
+    })
+}
+
+/// `(if (condition) (then))` or `(if (condition) (then) (else))`
+pub fn interpret_if(expressions: Vec<Expression>, span: Span) -> Result<Runnable, InterpretError> {
+    let mut operands = interpret_operands(expressions)?.into_iter().map(Box::new);
+
+    let (Some(condition), Some(then), otherwise, None) = (
+        operands.next(),
+        operands.next(),
+        operands.next(),
+        operands.next(),
+    ) else {
+        return Err(InterpretError::InvalidIf { span });
+    };
+
+    Ok(Runnable::Conditional {
+        conditional: Conditional {
+            condition,
+            then,
+            otherwise,
+        },
+    })
+}
+
+/// Interprets everything after the operator, each of which
+/// must be a list.
+fn interpret_operands(expressions: Vec<Expression>) -> Result<Vec<Runnable>, InterpretError> {
+    let (form, operands) = split_form(expressions);
+
+    operands
+        .map(|e| match e {
+            Expression::List(v, span) => interpret_list(v, span),
+            Expression::Atom(_, span) => Err(InterpretError::ExpectedCommand {
+                form: form.clone(),
+                span,
+            }),
+        })
+        .collect()
+}
+
+pub fn interpret_pipeline(
+    expressions: Vec<Expression>,
+    span: Span,
+) -> Result<Runnable, InterpretError> {
+    let mut pipeline = Pipeline::new();
 
-    match operator {
-        _ => interpret_command(expressions),
+    for e in expressions.into_iter().skip(1) {
+        let stage = match e {
+            Expression::List(v, span) => {
+                let stage = interpret_list(v, span)?;
+                if !stage.is_spawnable() {
+                    return Err(InterpretError::InvalidPipelineStage { span });
+                }
+                stage
+            }
+            Expression::Atom(_, span) => {
+                return Err(InterpretError::InvalidPipelineStage { span });
+            }
+        };
+
+        pipeline.stages.push(stage);
+    }
+
+    if pipeline.stages.is_empty() {
+        return Err(InterpretError::EmptyPipeline { span });
     }
+
+    Ok(Runnable::Pipeline { pipeline })
 }
 
-pub fn interpret_command(expressions: Vec<Expression>) -> Result<Runnable, InterpretError> {
+/// `(out (cat file.txt) output.txt)` or, without the inner list,
+/// `(in cat input.txt)` - everything between the operator and the
+/// file is the command being redirected.
+pub fn interpret_redirect(
+    kind: RedirectKind,
+    expressions: Vec<Expression>,
+    span: Span,
+) -> Result<Runnable, InterpretError> {
+    let (form, rest) = split_form(expressions);
+
+    let mut body: Vec<Expression> = rest.collect();
+    let target = match body.pop() {
+        Some(target) if !body.is_empty() => target,
+        _ => return Err(InterpretError::InvalidRedirect { form, span }),
+    };
+
+    let runnable = match <[Expression; 1]>::try_from(body) {
+        Ok([Expression::List(v, span)]) => interpret_list(v, span)?,
+        Ok(single) => interpret_command(single.into(), span)?,
+        Err(body) => interpret_command(body, span)?,
+    };
+
+    let target_span = target.span();
+    let target = interpret_clause(target)?;
+
+    Ok(Runnable::Redirect {
+        runnable: Box::new(runnable),
+        redirect: Box::new(Redirect {
+            kind,
+            target,
+            span: target_span,
+        }),
+    })
+}
+
+/// `span` is used for the command when it has no program
+pub fn interpret_command(
+    expressions: Vec<Expression>,
+    span: Span,
+) -> Result<Runnable, InterpretError> {
+    let span = expressions.first().map_or(span, Expression::span);
     let mut argv = vec![];
 
     for e in expressions {
-        match e {
-            Expression::Atom(a) => match a {
-                Atom::Literal(s) => argv.push(Clause::Literal(s)),
-                Atom::Symbol(s) => argv.push(Clause::Bare(s)),
-            },
-            Expression::List(v) => {
-                let executable = interpret_list(v)?;
-                argv.push(Clause::Embedded(executable));
-            }
-        }
+        argv.push(interpret_clause(e)?);
     }
 
     Ok(Runnable::Command {
-        command: Executable { argv },
+        command: Executable { argv, span },
     })
 }
 
+fn interpret_clause(expression: Expression) -> Result<Clause, InterpretError> {
+    match expression {
+        Expression::Atom(Atom::Literal(s), _) => Ok(Clause::Literal(s)),
+        Expression::Atom(Atom::Symbol(s), _) => Ok(Clause::Bare(s)),
+        Expression::List(v, span) => Ok(Clause::Embedded(interpret_list(v, span)?)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use nash_parser::parser::{Atom, Expression};
 
+    fn sym(s: &str) -> Expression {
+        Expression::Atom(Atom::Symbol(s.to_string()), Span::default())
+    }
+
+    fn lit(s: &str) -> Expression {
+        Expression::Atom(Atom::Literal(s.to_string()), Span::default())
+    }
+
+    fn list(expressions: Vec<Expression>) -> Expression {
+        Expression::List(expressions, Span::default())
+    }
+
     #[test]
     fn test_simple_command() {
-        let expressions = vec![Expression::List(vec![
-            Expression::Atom(Atom::Symbol("ls".to_string())),
-            Expression::Atom(Atom::Symbol("-la".to_string())),
-        ])];
+        let expressions = vec![list(vec![sym("ls"), sym("-la")])];
 
         let result = interpret(expressions).unwrap();
         assert_eq!(result.len(), 1);
@@ -81,15 +301,13 @@ mod tests {
                     _ => panic!("Expected Bare clause"),
                 }
             }
+            _ => panic!("Expected Command"),
         }
     }
 
     #[test]
     fn test_command_with_literal() {
-        let expressions = vec![Expression::List(vec![
-            Expression::Atom(Atom::Symbol("echo".to_string())),
-            Expression::Atom(Atom::Literal("hello world".to_string())),
-        ])];
+        let expressions = vec![list(vec![sym("echo"), lit("hello world")])];
 
         let result = interpret(expressions).unwrap();
         assert_eq!(result.len(), 1);
@@ -102,18 +320,16 @@ mod tests {
                     _ => panic!("Expected Literal clause"),
                 }
             }
+            _ => panic!("Expected Command"),
         }
     }
 
     #[test]
     fn test_nested_command() {
-        let expressions = vec![Expression::List(vec![
-            Expression::Atom(Atom::Symbol("ls".to_string())),
-            Expression::Atom(Atom::Symbol("-la".to_string())),
-            Expression::List(vec![
-                Expression::Atom(Atom::Symbol("cat".to_string())),
-                Expression::Atom(Atom::Symbol("file.txt".to_string())),
-            ]),
+        let expressions = vec![list(vec![
+            sym("ls"),
+            sym("-la"),
+            list(vec![sym("cat"), sym("file.txt")]),
         ])];
 
         let result = interpret(expressions).unwrap();
@@ -133,19 +349,18 @@ mod tests {
                                 _ => panic!("Expected Bare clause"),
                             }
                         }
+                        _ => panic!("Expected inner Command"),
                     },
                     _ => panic!("Expected Embedded clause"),
                 }
             }
+            _ => panic!("Expected Command"),
         }
     }
 
     #[test]
     fn test_multiple_top_level_commands() {
-        let expressions = vec![
-            Expression::List(vec![Expression::Atom(Atom::Symbol("pwd".to_string()))]),
-            Expression::List(vec![Expression::Atom(Atom::Symbol("ls".to_string()))]),
-        ];
+        let expressions = vec![list(vec![sym("pwd")]), list(vec![sym("ls")])];
 
         let result = interpret(expressions).unwrap();
         assert_eq!(result.len(), 2);
@@ -153,27 +368,21 @@ mod tests {
 
     #[test]
     fn test_top_level_atom_error() {
-        let expressions = vec![Expression::Atom(Atom::Symbol("not-a-list".to_string()))];
+        let expressions = vec![sym("not-a-list")];
 
         let result = interpret(expressions);
         assert!(result.is_err());
         match result {
-            Err(InterpretError::TopLevelAtom) => {}
+            Err(InterpretError::TopLevelAtom { .. }) => {}
             _ => panic!("Expected TopLevelAtom error"),
         }
     }
 
     #[test]
     fn test_deeply_nested_commands() {
-        let expressions = vec![Expression::List(vec![
-            Expression::Atom(Atom::Symbol("echo".to_string())),
-            Expression::List(vec![
-                Expression::Atom(Atom::Symbol("cat".to_string())),
-                Expression::List(vec![
-                    Expression::Atom(Atom::Symbol("ls".to_string())),
-                    Expression::Atom(Atom::Symbol("/tmp".to_string())),
-                ]),
-            ]),
+        let expressions = vec![list(vec![
+            sym("echo"),
+            list(vec![sym("cat"), list(vec![sym("ls"), sym("/tmp")])]),
         ])];
 
         let result = interpret(expressions).unwrap();
@@ -188,6 +397,338 @@ mod tests {
                     _ => panic!("Expected Embedded clause at depth 1"),
                 }
             }
+            _ => panic!("Expected Command"),
+        }
+    }
+
+    #[test]
+    fn test_pipe_form() {
+        let expressions = vec![list(vec![
+            sym("pipe"),
+            list(vec![sym("cat"), sym("file.txt")]),
+            list(vec![sym("grep"), sym("error")]),
+            list(vec![sym("sort")]),
+        ])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Pipeline { pipeline } => {
+                assert_eq!(pipeline.stages.len(), 3);
+                assert!(pipeline.stages.iter().all(Runnable::is_spawnable));
+            }
+            _ => panic!("Expected Pipeline"),
+        }
+    }
+
+    #[test]
+    fn test_pipe_shorthand() {
+        let expressions = vec![list(vec![
+            sym("|"),
+            list(vec![sym("ls")]),
+            list(vec![sym("wc"), sym("-l")]),
+        ])];
+
+        let result = interpret(expressions).unwrap();
+        assert!(matches!(result[0], Runnable::Pipeline { .. }));
+    }
+
+    #[test]
+    fn test_quoted_pipe_is_a_command() {
+        let expressions = vec![list(vec![lit("pipe"), list(vec![sym("ls")])])];
+
+        let result = interpret(expressions).unwrap();
+        assert!(matches!(result[0], Runnable::Command { .. }));
+    }
+
+    #[test]
+    fn test_pipe_rejects_atom_stage() {
+        let expressions = vec![list(vec![sym("pipe"), list(vec![sym("ls")]), sym("grep")])];
+
+        assert!(matches!(
+            interpret(expressions),
+            Err(InterpretError::InvalidPipelineStage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_empty_pipe_error() {
+        let expressions = vec![list(vec![sym("pipe")])];
+
+        assert!(matches!(
+            interpret(expressions),
+            Err(InterpretError::EmptyPipeline { .. })
+        ));
+    }
+
+    #[test]
+    fn test_out_form_with_inner_list() {
+        let expressions = vec![list(vec![
+            sym("out"),
+            list(vec![sym("cat"), sym("file.txt")]),
+            sym("output.txt"),
+        ])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Redirect { runnable, redirect } => {
+                assert!(matches!(**runnable, Runnable::Command { .. }));
+                assert_eq!(redirect.kind, RedirectKind::Out);
+                match &redirect.target {
+                    Clause::Bare(s) => assert_eq!(s, "output.txt"),
+                    _ => panic!("Expected Bare target"),
+                }
+            }
+            _ => panic!("Expected Redirect"),
         }
     }
+
+    #[test]
+    fn test_in_form_with_bare_command() {
+        let expressions = vec![list(vec![sym("<"), sym("cat"), lit("input file.txt")])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Redirect { runnable, redirect } => {
+                assert_eq!(redirect.kind, RedirectKind::In);
+                match &**runnable {
+                    Runnable::Command { command } => assert_eq!(command.argv.len(), 1),
+                    _ => panic!("Expected Command"),
+                }
+                match &redirect.target {
+                    Clause::Literal(s) => assert_eq!(s, "input file.txt"),
+                    _ => panic!("Expected Literal target"),
+                }
+            }
+            _ => panic!("Expected Redirect"),
+        }
+    }
+
+    #[test]
+    fn test_nested_redirects() {
+        let expressions = vec![list(vec![
+            sym(">>"),
+            list(vec![sym("in"), sym("cat"), sym("input.txt")]),
+            sym("output.txt"),
+        ])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Redirect { runnable, redirect } => {
+                assert_eq!(redirect.kind, RedirectKind::Append);
+                assert!(matches!(**runnable, Runnable::Redirect { .. }));
+                assert!(result[0].is_spawnable());
+            }
+            _ => panic!("Expected Redirect"),
+        }
+    }
+
+    #[test]
+    fn test_redirect_without_file_error() {
+        let expressions = vec![list(vec![sym("out"), list(vec![sym("ls")])])];
+
+        match interpret(expressions) {
+            Err(InterpretError::InvalidRedirect { form, .. }) => assert_eq!(form, "out"),
+            _ => panic!("Expected InvalidRedirect error"),
+        }
+    }
+
+    #[test]
+    fn test_def_form() {
+        let expressions = vec![list(vec![sym("def"), sym("ls"), lit("ls -la")])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Binding { name, value } => {
+                assert_eq!(name, "ls");
+                match &**value {
+                    Clause::Literal(s) => assert_eq!(s, "ls -la"),
+                    _ => panic!("Expected Literal value"),
+                }
+            }
+            _ => panic!("Expected Binding"),
+        }
+    }
+
+    #[test]
+    fn test_def_with_embedded_value() {
+        let expressions = vec![list(vec![sym("def"), sym("here"), list(vec![sym("pwd")])])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Binding { value, .. } => {
+                assert!(matches!(**value, Clause::Embedded(_)))
+            }
+            _ => panic!("Expected Binding"),
+        }
+    }
+
+    #[test]
+    fn test_def_arity_error() {
+        let expressions = vec![list(vec![sym("def"), sym("x")])];
+
+        assert!(matches!(
+            interpret(expressions),
+            Err(InterpretError::InvalidDef { .. })
+        ));
+    }
+
+    #[test]
+    fn test_and_or_forms() {
+        for (form, is_and) in [("and", true), ("or", false)] {
+            let expressions = vec![list(vec![
+                sym(form),
+                list(vec![sym("cargo"), sym("build")]),
+                list(vec![sym("./deploy")]),
+            ])];
+
+            let result = interpret(expressions).unwrap();
+            match &result[0] {
+                Runnable::Logical {
+                    logical: Logical::And(operands),
+                } if is_and => assert_eq!(operands.len(), 2),
+                Runnable::Logical {
+                    logical: Logical::Or(operands),
+                } if !is_and => assert_eq!(operands.len(), 2),
+                _ => panic!("Expected {form}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_not_form() {
+        let expressions = vec![list(vec![sym("not"), list(vec![sym("false")])])];
+
+        let result = interpret(expressions).unwrap();
+        assert!(matches!(
+            result[0],
+            Runnable::Logical {
+                logical: Logical::Not(_)
+            }
+        ));
+    }
+
+    #[test]
+    fn test_not_arity_error() {
+        let expressions = vec![list(vec![
+            sym("not"),
+            list(vec![sym("true")]),
+            list(vec![sym("false")]),
+        ])];
+
+        assert!(matches!(
+            interpret(expressions),
+            Err(InterpretError::InvalidNot { .. })
+        ));
+    }
+
+    #[test]
+    fn test_logical_operand_must_be_list() {
+        let expressions = vec![list(vec![
+            sym("and"),
+            list(vec![sym("true")]),
+            sym("false"),
+        ])];
+
+        match interpret(expressions) {
+            Err(InterpretError::ExpectedCommand { form, .. }) => assert_eq!(form, "and"),
+            _ => panic!("Expected ExpectedCommand error"),
+        }
+    }
+
+    #[test]
+    fn test_if_form() {
+        let expressions = vec![list(vec![
+            sym("if"),
+            list(vec![sym("test"), sym("-f"), sym("x")]),
+            list(vec![sym("cat"), sym("x")]),
+            list(vec![sym("touch"), sym("x")]),
+        ])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Conditional { conditional } => {
+                assert!(matches!(*conditional.condition, Runnable::Command { .. }));
+                assert!(conditional.otherwise.is_some());
+            }
+            _ => panic!("Expected Conditional"),
+        }
+    }
+
+    #[test]
+    fn test_if_without_else() {
+        let expressions = vec![list(vec![
+            sym("if"),
+            list(vec![sym("true")]),
+            list(vec![sym("ls")]),
+        ])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Conditional { conditional } => assert!(conditional.otherwise.is_none()),
+            _ => panic!("Expected Conditional"),
+        }
+    }
+
+    #[test]
+    fn test_if_nested_in_command() {
+        let expressions = vec![list(vec![
+            sym("echo"),
+            list(vec![
+                sym("if"),
+                list(vec![sym("true")]),
+                list(vec![sym("echo"), sym("yes")]),
+            ]),
+        ])];
+
+        let result = interpret(expressions).unwrap();
+        match &result[0] {
+            Runnable::Command { command } => match &command.argv[1] {
+                Clause::Embedded(Runnable::Conditional { .. }) => {}
+                _ => panic!("Expected embedded Conditional"),
+            },
+            _ => panic!("Expected Command"),
+        }
+    }
+
+    #[test]
+    fn test_if_arity_errors() {
+        let too_few = vec![list(vec![sym("if"), list(vec![sym("true")])])];
+        assert!(matches!(
+            interpret(too_few),
+            Err(InterpretError::InvalidIf { .. })
+        ));
+
+        let too_many = vec![list(vec![
+            sym("if"),
+            list(vec![sym("true")]),
+            list(vec![sym("a")]),
+            list(vec![sym("b")]),
+            list(vec![sym("c")]),
+        ])];
+        assert!(matches!(
+            interpret(too_many),
+            Err(InterpretError::InvalidIf { .. })
+        ));
+    }
+
+    #[test]
+    fn test_errors_report_offending_span() {
+        let parsed = nash_parser::parser::parse("(ls)\n(pipe (ls) grep)").unwrap();
+
+        let err = interpret(parsed).err().unwrap();
+        assert!(matches!(err, InterpretError::InvalidPipelineStage { .. }));
+        let span = err.span();
+        assert_eq!(
+            (span.start, span.end, span.line, span.column),
+            (16, 20, 2, 12)
+        );
+    }
+
+    #[test]
+    fn test_form_shape_errors_span_the_form() {
+        let parsed = nash_parser::parser::parse("(if (true))").unwrap();
+
+        let err = interpret(parsed).err().unwrap();
+        assert_eq!((err.span().start, err.span().end), (0, 11));
+    }
 }
@@ -1,2 +1,3 @@
+pub mod diagnostic;
 mod interpret;
 pub mod runner;
@@ -1,21 +1,50 @@
+pub mod conditional;
+pub mod environment;
 pub mod executable;
+pub mod logical;
+pub mod pipeline;
+pub mod redirect;
 
-use std::io::{self, Write};
+use std::{
+    fs::File,
+    io::{self, Write},
+    process::{Child, Stdio},
+    rc::Rc,
+};
 
-use executable::Executable;
-use nash_parser::parser;
+use conditional::Conditional;
+use environment::Environment;
+use executable::{Clause, Executable};
+use logical::Logical;
+use nash_parser::{parser, span::Span};
+use pipeline::Pipeline;
+use redirect::Redirect;
 use thiserror::Error;
 
-use crate::interpret::interpret;
+use crate::{diagnostic::Diagnostic, interpret::interpret};
 
 #[derive(Debug)]
 pub enum Runnable {
-    Command { command: Executable },
-    // coming soon
-    // Pipeline
-    // Binding
-    // Logical operators
-    // ...
+    Command {
+        command: Executable,
+    },
+    Pipeline {
+        pipeline: Pipeline,
+    },
+    Redirect {
+        runnable: Box<Runnable>,
+        redirect: Box<Redirect>,
+    },
+    Binding {
+        name: String,
+        value: Box<Clause>,
+    },
+    Logical {
+        logical: Logical,
+    },
+    Conditional {
+        conditional: Conditional,
+    },
 }
 
 #[derive(Clone)]
@@ -27,20 +56,67 @@ pub enum RunKind {
 #[derive(Clone)]
 pub struct RunContext {
     pub kind: RunKind,
+    pub env: Environment,
+    /// File standing in for stdin, set by an enclosing `in`
+    pub stdin: Option<Rc<File>>,
+    /// File standing in for stdout, set by an enclosing `out` or `append`
+    pub stdout: Option<Rc<File>>,
+    /// Where the input being run came from, like a script's path
+    pub origin: Rc<str>,
+    /// The input being run, for reporting errors against as they
+    /// happen
+    pub source: Rc<str>,
 }
 
 impl RunContext {
+    /// The context an embedded clause runs in. Like a `$(...)`
+    /// subshell, its `def`s go in a scope of their own and are gone
+    /// once it finishes.
     pub fn as_embedded(&self) -> Self {
-        let mut ctx = self.clone();
+        let mut ctx = self.without_redirects();
         ctx.kind = RunKind::Embedded;
+        ctx.env = self.env.child();
+        ctx
+    }
+
+    pub fn without_redirects(&self) -> Self {
+        let mut ctx = self.clone();
+        ctx.stdin = None;
+        ctx.stdout = None;
         ctx
     }
+
+    /// The redirected stdin if there is one, otherwise `fallback`
+    pub fn stdin_or(&self, fallback: Stdio) -> io::Result<Stdio> {
+        match &self.stdin {
+            Some(file) => Ok(Stdio::from(file.try_clone()?)),
+            None => Ok(fallback),
+        }
+    }
+
+    /// The redirected stdout if there is one, otherwise `fallback`
+    pub fn stdout_or(&self, fallback: Stdio) -> io::Result<Stdio> {
+        match &self.stdout {
+            Some(file) => Ok(Stdio::from(file.try_clone()?)),
+            None => Ok(fallback),
+        }
+    }
+
+    /// Renders `e` against the input being run
+    pub fn report(&self, e: &io::Error) -> String {
+        Diagnostic::from(e).render(&self.origin, &self.source)
+    }
 }
 
 impl Default for RunContext {
     fn default() -> Self {
         Self {
             kind: RunKind::Interactive,
+            env: Environment::new(),
+            stdin: None,
+            stdout: None,
+            origin: Rc::from("<input>"),
+            source: Rc::from(""),
         }
     }
 }
@@ -49,6 +125,28 @@ pub struct SuccessfulRun {
     out: Vec<u8>,
     err: Vec<u8>,
     code: i32,
+    stage_codes: Vec<i32>,
+}
+
+impl SuccessfulRun {
+    fn new(out: Vec<u8>, err: Vec<u8>, code: i32) -> Self {
+        SuccessfulRun {
+            out,
+            err,
+            code,
+            stage_codes: vec![code],
+        }
+    }
+
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    /// Exit code of every stage of the last pipeline that ran.
+    /// A plain command has exactly one.
+    pub fn stage_codes(&self) -> &[i32] {
+        &self.stage_codes
+    }
 }
 
 impl Runnable {
@@ -59,8 +157,130 @@ impl Runnable {
     pub fn run_in_context(self, ctx: RunContext) -> io::Result<SuccessfulRun> {
         match self {
             Runnable::Command { command } => command.execute(ctx),
+            Runnable::Pipeline { pipeline } => pipeline.execute(ctx),
+            Runnable::Redirect { runnable, redirect } => {
+                let ctx = redirect.apply(ctx)?;
+                runnable.run_in_context(ctx)
+            }
+            Runnable::Binding { name, value } => {
+                let value = value.evaluate(&ctx)?;
+                ctx.env.define(name, value);
+                Ok(SuccessfulRun::new(vec![], vec![], 0))
+            }
+            Runnable::Logical { logical } => logical.execute(ctx),
+            Runnable::Conditional { conditional } => conditional.execute(ctx),
+        }
+    }
+
+    /// Runs the runnable for its exit status, as the operands of
+    /// `and`, `or` and `not` and the condition of `if` are. A
+    /// command that can't be started fails the way it would in any
+    /// shell, with its status and the reason on stderr, rather than
+    /// ending the whole form. Interactively the reason is written
+    /// straight away, ahead of anything that runs after it.
+    pub fn run_for_status(self, ctx: RunContext) -> io::Result<SuccessfulRun> {
+        let reporting = ctx.clone();
+
+        self.run_in_context(ctx).or_else(|e| {
+            let Some(code) = spawn_status(&e) else {
+                return Err(e);
+            };
+
+            let report = reporting.report(&e);
+            match reporting.kind {
+                RunKind::Interactive => {
+                    io::stderr().write_all(report.as_bytes())?;
+                    Ok(SuccessfulRun::new(vec![], vec![], code))
+                }
+                RunKind::Embedded => Ok(SuccessfulRun::new(vec![], report.into_bytes(), code)),
+            }
+        })
+    }
+
+    /// Starts the runnable as a child process without waiting on it.
+    /// Only commands, optionally redirected, can be spawned - used to
+    /// wire up pipeline stages. Redirections in `ctx` win over the
+    /// streams passed in.
+    pub fn spawn(
+        self,
+        ctx: &RunContext,
+        stdin: Stdio,
+        stdout: Stdio,
+        stderr: Stdio,
+    ) -> io::Result<Child> {
+        match self {
+            Runnable::Command { command } => {
+                let span = command.span;
+                let mut cmd = command.into_command(ctx)?;
+                cmd.stdin(ctx.stdin_or(stdin)?)
+                    .stdout(ctx.stdout_or(stdout)?)
+                    .stderr(stderr);
+                cmd.spawn()
+                    .map_err(|e| executable::spawn_error(&cmd, span, e))
+            }
+            Runnable::Redirect { runnable, redirect } => {
+                let ctx = redirect.apply(ctx.clone())?;
+                runnable.spawn(&ctx, stdin, stdout, stderr)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only commands can be pipeline stages",
+            )),
         }
     }
+
+    /// Whether the runnable is a single process, see [`Runnable::spawn`]
+    pub fn is_spawnable(&self) -> bool {
+        match self {
+            Runnable::Command { .. } => true,
+            Runnable::Redirect { runnable, .. } => runnable.is_spawnable(),
+            _ => false,
+        }
+    }
+}
+
+/// Payload of an io error raised while running, recording which
+/// part of the input was being run when it happened
+#[derive(Error, Debug)]
+#[error("{message}")]
+pub struct LocatedError {
+    pub message: String,
+    pub span: Span,
+    /// The exit status the error stands for, set when a command
+    /// couldn't be started
+    pub status: Option<i32>,
+}
+
+/// Attaches `span` to `e`, keeping its kind. Errors that already
+/// have a span keep it - the innermost location is the useful one.
+pub fn locate(e: io::Error, span: Span) -> io::Error {
+    if error_span(&e).is_some() {
+        return e;
+    }
+
+    io::Error::new(
+        e.kind(),
+        LocatedError {
+            message: e.to_string(),
+            span,
+            status: None,
+        },
+    )
+}
+
+/// The span attached to `e` by [`locate`], if any
+pub fn error_span(e: &io::Error) -> Option<Span> {
+    e.get_ref()
+        .and_then(|inner| inner.downcast_ref::<LocatedError>())
+        .map(|located| located.span)
+}
+
+/// The exit status `e` stands for, when it comes from a command that
+/// couldn't be started, see [`executable::spawn_error`]
+pub fn spawn_status(e: &io::Error) -> Option<i32> {
+    e.get_ref()
+        .and_then(|inner| inner.downcast_ref::<LocatedError>())
+        .and_then(|located| located.status)
 }
 
 #[derive(Error, Debug)]
@@ -75,15 +295,114 @@ pub enum RunnerError {
     Io(#[from] io::Error),
 }
 
-pub fn run(input: &str) -> Result<(), RunnerError> {
+impl RunnerError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            RunnerError::Parse(e) => e.into(),
+            RunnerError::Interpret(e) => e.into(),
+            RunnerError::Io(e) => e.into(),
+        }
+    }
+
+    /// Renders the error against the input that caused it,
+    /// see [`Diagnostic::render`]
+    pub fn report(&self, origin: &str, source: &str) -> String {
+        self.diagnostic().render(origin, source)
+    }
+
+    /// Exit status to report for the failed input, following the
+    /// usual shell conventions. Only a command that couldn't start
+    /// gets 127 or 126 - a missing redirect file is an ordinary
+    /// failure.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RunnerError::Parse(_) | RunnerError::Interpret(_) => 2,
+            RunnerError::Io(e) => spawn_status(e).unwrap_or(1),
+        }
+    }
+}
+
+/// Runs `input`, returning the exit code of the last runnable
+pub fn run(input: &str) -> Result<i32, RunnerError> {
+    run_in_context(input, &RunContext::default())
+}
+
+/// Runs `input` against an existing context, so bindings made
+/// with `def` persist from one call to the next.
+pub fn run_in_context(input: &str, ctx: &RunContext) -> Result<i32, RunnerError> {
     let parsed = parser::parse(input)?;
     let runnables = interpret(parsed)?;
+    let ctx = RunContext {
+        source: Rc::from(input),
+        ..ctx.clone()
+    };
+    let mut code = 0;
+
     for runnable in runnables {
-        let result = runnable.run()?;
+        let result = runnable.run_in_context(ctx.clone())?;
         io::stdout().write_all(&result.out)?;
         io::stderr().write_all(&result.err)?;
         io::stdout().flush()?;
+        code = result.code;
+    }
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_returns_last_exit_code() {
+        assert_eq!(run("(true) (false)").unwrap(), 1);
+        assert_eq!(run("(false) (true)").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_empty_input_exits_zero() {
+        assert_eq!(run("; nothing but a comment").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_bindings_persist_across_runs_in_context() {
+        let ctx = RunContext::default();
+        run_in_context("(def check 'test -n')", &ctx).unwrap();
+        assert_eq!(run_in_context("(check value)", &ctx).unwrap(), 0);
     }
 
-    Ok(())
+    #[test]
+    fn test_embedded_clauses_scope_their_bindings() {
+        let ctx = RunContext::default();
+        run_in_context("(def x outer)", &ctx).unwrap();
+
+        assert_eq!(run_in_context("(test (echo x) = outer)", &ctx).unwrap(), 0);
+        run_in_context("(echo (def x inner) (def y inner))", &ctx).unwrap();
+
+        assert_eq!(ctx.env.lookup("x"), Some("outer".to_string()));
+        assert_eq!(ctx.env.lookup("y"), None);
+    }
+
+    #[test]
+    fn test_missing_command_names_the_program() {
+        let err = run("(nonexistent_command_xyz)").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "command not found: nonexistent_command_xyz"
+        );
+        assert_eq!(err.exit_code(), 127);
+    }
+
+    #[test]
+    fn test_redirect_failure_is_not_command_not_found() {
+        let err = run("(out (echo) /nonexistent/dir/x)").err().unwrap();
+        assert_eq!(err.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_parse_error_exit_code() {
+        let err = run("(ls").err().unwrap();
+        assert!(matches!(err, RunnerError::Parse(_)));
+        assert_eq!(err.exit_code(), 2);
+    }
 }
@@ -0,0 +1,180 @@
+use crate::runner::{RunContext, Runnable, SuccessfulRun};
+use std::{fs::OpenOptions, io, rc::Rc};
+
+/// `(if condition then else)` - runs `then` when the condition
+/// exits 0, otherwise `otherwise` if there is one.
+///
+/// Only the chosen branch is the value of the form, so whether run
+/// interactively or embedded, what the condition writes to stdout
+/// is thrown away. Its stderr is left alone.
+#[derive(Debug)]
+pub struct Conditional {
+    pub condition: Box<Runnable>,
+    pub then: Box<Runnable>,
+    pub otherwise: Option<Box<Runnable>>,
+}
+
+impl Conditional {
+    pub fn execute(self, ctx: RunContext) -> io::Result<SuccessfulRun> {
+        let null = OpenOptions::new().write(true).open("/dev/null")?;
+        let condition_ctx = RunContext {
+            stdout: Some(Rc::new(null)),
+            ..ctx.clone()
+        };
+        let condition = self.condition.run_for_status(condition_ctx)?;
+
+        let branch = if condition.code == 0 {
+            Some(self.then)
+        } else {
+            self.otherwise
+        };
+
+        let mut result = match branch {
+            Some(branch) => branch.run_in_context(ctx)?,
+            None => SuccessfulRun::new(vec![], vec![], 0),
+        };
+        result.err.splice(0..0, condition.err);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{
+        RunKind,
+        executable::{Clause, Executable},
+    };
+    use std::fs::{self, File};
+    use tempfile::tempdir;
+
+    fn embedded_ctx() -> RunContext {
+        RunContext {
+            kind: RunKind::Embedded,
+            ..Default::default()
+        }
+    }
+
+    fn command(argv: &[&str]) -> Box<Runnable> {
+        Box::new(Runnable::Command {
+            command: Executable {
+                argv: argv
+                    .iter()
+                    .map(|s| Clause::Literal(s.to_string()))
+                    .collect(),
+                ..Default::default()
+            },
+        })
+    }
+
+    fn run(conditional: Conditional) -> SuccessfulRun {
+        conditional.execute(embedded_ctx()).unwrap()
+    }
+
+    #[test]
+    fn test_true_condition_takes_then_branch() {
+        let result = run(Conditional {
+            condition: command(&["true"]),
+            then: command(&["echo", "yes"]),
+            otherwise: Some(command(&["echo", "no"])),
+        });
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "yes\n");
+    }
+
+    #[test]
+    fn test_false_condition_takes_else_branch() {
+        let result = run(Conditional {
+            condition: command(&["false"]),
+            then: command(&["echo", "yes"]),
+            otherwise: Some(command(&["sh", "-c", "echo no; exit 3"])),
+        });
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "no\n");
+        assert_eq!(result.code, 3);
+    }
+
+    #[test]
+    fn test_false_condition_without_else() {
+        let result = run(Conditional {
+            condition: command(&["false"]),
+            then: command(&["echo", "yes"]),
+            otherwise: None,
+        });
+
+        assert!(result.out.is_empty());
+        assert_eq!(result.code, 0);
+    }
+
+    #[test]
+    fn test_embedded_condition_output_is_not_the_value() {
+        let result = run(Conditional {
+            condition: command(&["echo", "condition"]),
+            then: command(&["echo", "branch"]),
+            otherwise: None,
+        });
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "branch\n");
+    }
+
+    #[test]
+    fn test_interactive_condition_output_is_dropped() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("out");
+        let ctx = RunContext {
+            kind: RunKind::Interactive,
+            stdout: Some(Rc::new(File::create(&path).unwrap())),
+            ..Default::default()
+        };
+
+        let conditional = Conditional {
+            condition: command(&["echo", "condition"]),
+            then: command(&["echo", "branch"]),
+            otherwise: None,
+        };
+        conditional.execute(ctx).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "branch\n");
+    }
+
+    #[test]
+    fn test_missing_condition_command_takes_else_branch() {
+        let result = run(Conditional {
+            condition: command(&["nonexistent_command_xyz"]),
+            then: command(&["echo", "yes"]),
+            otherwise: Some(command(&["echo", "no"])),
+        });
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "no\n");
+        assert_eq!(
+            String::from_utf8_lossy(&result.err),
+            concat!(
+                "error: command not found: nonexistent_command_xyz\n",
+                " --> <input>:1:1\n",
+                "  |\n",
+                "1 | \n",
+                "  | ^ not found\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_value_flows_into_embedded_clause() {
+        let conditional = Runnable::Conditional {
+            conditional: Conditional {
+                condition: command(&["false"]),
+                then: command(&["echo", "yes"]),
+                otherwise: Some(command(&["echo", "no"])),
+            },
+        };
+
+        let mut echo = Executable::new();
+        echo.argv.push(Clause::Literal("echo".to_string()));
+        echo.argv.push(Clause::Literal("answer:".to_string()));
+        echo.argv.push(Clause::Embedded(conditional));
+
+        let result = echo.execute(embedded_ctx()).unwrap();
+        assert_eq!(String::from_utf8_lossy(&result.out), "answer: no\n");
+    }
+}
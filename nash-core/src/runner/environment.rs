@@ -0,0 +1,151 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// Symbol bindings made with `def`.
+///
+/// Scopes chain to their parent - lookups walk outward until a
+/// binding is found, definitions always land in the innermost scope.
+/// Cloning an `Environment` shares the scope rather than copying it.
+#[derive(Clone, Debug, Default)]
+pub struct Environment {
+    scope: Rc<Scope>,
+}
+
+#[derive(Debug, Default)]
+struct Scope {
+    bindings: RefCell<HashMap<String, String>>,
+    parent: Option<Rc<Scope>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A new innermost scope whose bindings shadow, but never
+    /// overwrite, those of `self`
+    pub fn child(&self) -> Self {
+        Environment {
+            scope: Rc::new(Scope {
+                bindings: RefCell::new(HashMap::new()),
+                parent: Some(Rc::clone(&self.scope)),
+            }),
+        }
+    }
+
+    pub fn define(&self, name: impl Into<String>, value: impl Into<String>) {
+        self.scope
+            .bindings
+            .borrow_mut()
+            .insert(name.into(), value.into());
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<String> {
+        let mut scope = Some(&self.scope);
+
+        while let Some(current) = scope {
+            if let Some(value) = current.bindings.borrow().get(name) {
+                return Some(value.clone());
+            }
+            scope = current.parent.as_ref();
+        }
+
+        None
+    }
+
+    /// The value bound to `name`, or `name` itself when unbound
+    pub fn resolve(&self, name: &str) -> String {
+        self.lookup(name).unwrap_or_else(|| name.to_owned())
+    }
+
+    /// Every name bound here or in an outer scope, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![];
+        let mut scope = Some(&self.scope);
+
+        while let Some(current) = scope {
+            names.extend(current.bindings.borrow().keys().cloned());
+            scope = current.parent.as_ref();
+        }
+
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unbound_lookup_is_none() {
+        let env = Environment::new();
+        assert_eq!(env.lookup("ls"), None);
+    }
+
+    #[test]
+    fn test_unbound_resolves_to_name() {
+        let env = Environment::new();
+        assert_eq!(env.resolve("ls"), "ls");
+    }
+
+    #[test]
+    fn test_define_then_lookup() {
+        let env = Environment::new();
+        env.define("ls", "ls -la");
+        assert_eq!(env.lookup("ls"), Some("ls -la".to_string()));
+    }
+
+    #[test]
+    fn test_redefine_overwrites() {
+        let env = Environment::new();
+        env.define("x", "1");
+        env.define("x", "2");
+        assert_eq!(env.resolve("x"), "2");
+    }
+
+    #[test]
+    fn test_clones_share_bindings() {
+        let env = Environment::new();
+        let clone = env.clone();
+        clone.define("x", "1");
+        assert_eq!(env.resolve("x"), "1");
+    }
+
+    #[test]
+    fn test_child_sees_parent_bindings() {
+        let parent = Environment::new();
+        parent.define("x", "1");
+
+        let child = parent.child();
+        assert_eq!(child.resolve("x"), "1");
+    }
+
+    #[test]
+    fn test_child_shadows_without_leaking() {
+        let parent = Environment::new();
+        parent.define("x", "1");
+
+        let child = parent.child();
+        child.define("x", "2");
+        child.define("y", "3");
+
+        assert_eq!(child.resolve("x"), "2");
+        assert_eq!(parent.resolve("x"), "1");
+        assert_eq!(parent.lookup("y"), None);
+    }
+
+    #[test]
+    fn test_names_cover_every_scope() {
+        let parent = Environment::new();
+        parent.define("x", "1");
+        parent.define("y", "2");
+
+        let child = parent.child();
+        child.define("x", "3");
+        child.define("z", "4");
+
+        assert_eq!(child.names(), vec!["x", "y", "z"]);
+        assert_eq!(parent.names(), vec!["x", "y"]);
+    }
+}
@@ -1,12 +1,18 @@
-use crate::runner::{RunContext, RunKind, Runnable, SuccessfulRun};
+use crate::runner::{LocatedError, RunContext, RunKind, Runnable, SuccessfulRun};
+use nash_parser::span::Span;
+use nash_path::SearchPath;
 use std::{
     io,
+    os::unix::process::CommandExt,
+    path::PathBuf,
     process::{Command as StdCommand, Stdio},
 };
 
 #[derive(Debug, Default)]
 pub struct Executable {
     pub argv: Vec<Clause>,
+    /// Where the program was written, for error reporting
+    pub span: Span,
 }
 
 #[derive(Debug)]
@@ -18,47 +24,94 @@ pub enum Clause {
 
 impl Executable {
     pub fn new() -> Self {
-        Executable { argv: vec![] }
+        Executable {
+            argv: vec![],
+            span: Span::default(),
+        }
     }
 
     pub fn execute(self, ctx: RunContext) -> io::Result<SuccessfulRun> {
         if self.argv.is_empty() {
-            return Ok(SuccessfulRun {
-                out: vec![],
-                err: vec![],
-                code: 0,
-            });
+            return Ok(SuccessfulRun::new(vec![], vec![], 0));
+        }
+
+        let span = self.span;
+        let mut cmd = self.into_command(&ctx)?;
+
+        match ctx.kind {
+            RunKind::Embedded => {
+                let output = cmd
+                    .stdin(ctx.stdin_or(Stdio::null())?)
+                    .stdout(ctx.stdout_or(Stdio::piped())?)
+                    .stderr(Stdio::piped())
+                    .output()
+                    .map_err(|e| spawn_error(&cmd, span, e))?;
+                Ok(SuccessfulRun::new(
+                    output.stdout,
+                    output.stderr,
+                    output.status.code().unwrap_or(-1),
+                ))
+            }
+            RunKind::Interactive => {
+                let status = cmd
+                    .stdin(ctx.stdin_or(Stdio::inherit())?)
+                    .stdout(ctx.stdout_or(Stdio::inherit())?)
+                    .stderr(Stdio::inherit())
+                    .status()
+                    .map_err(|e| spawn_error(&cmd, span, e))?;
+
+                Ok(SuccessfulRun::new(
+                    vec![],
+                    vec![],
+                    status.code().unwrap_or(-1),
+                ))
+            }
         }
+    }
 
+    /// Builds the process to run, evaluating any embedded clauses
+    /// along the way. Nothing is spawned for the command itself.
+    pub fn into_command(self, ctx: &RunContext) -> io::Result<StdCommand> {
         let mut argv = self.argv;
+        if argv.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "No command specified",
+            ));
+        }
+
         let first = argv.remove(0);
 
         let program = match first {
-            Clause::Bare(s) | Clause::Literal(s) => s,
+            Clause::Literal(s) => s,
+            Clause::Bare(s) => match ctx.env.lookup(&s) {
+                Some(value) => split_program(&value, &mut argv)?,
+                None => s,
+            },
             Clause::Embedded(runnable) => {
                 let result = runnable.run_in_context(ctx.as_embedded())?;
-
-                let output_str = String::from_utf8_lossy(&result.out);
-                let mut parts = output_str.split_whitespace();
-
-                let program = parts.next().ok_or_else(|| {
-                    io::Error::new(io::ErrorKind::InvalidInput, "No command specified")
-                })?;
-
-                // Remaining words become leading args
-                argv.splice(0..0, parts.map(|s| Clause::Literal(s.to_string())));
-
-                program.to_string()
+                split_program(&String::from_utf8_lossy(&result.out), &mut argv)?
             }
         };
 
-        let mut cmd = StdCommand::new(&program);
+        // Resolved the way completion does, so what's offered is what
+        // runs. Spawning a path doesn't search `PATH` again, so this
+        // is the only walk. Unresolved names are left for spawning to
+        // report.
+        let path = SearchPath::from_env()
+            .which(&program)
+            .unwrap_or_else(|| PathBuf::from(&program));
+        let mut cmd = StdCommand::new(path);
+        cmd.arg0(&program);
 
         for clause in argv {
             match clause {
-                Clause::Bare(s) | Clause::Literal(s) => {
+                Clause::Literal(s) => {
                     cmd.arg(s);
                 }
+                Clause::Bare(s) => {
+                    cmd.arg(ctx.env.resolve(&s));
+                }
                 Clause::Embedded(runnable) => {
                     let result = runnable.run_in_context(ctx.as_embedded())?;
                     let output_str = String::from_utf8_lossy(&result.out);
@@ -69,32 +122,64 @@ impl Executable {
             }
         }
 
-        match ctx.kind {
-            RunKind::Embedded => {
-                let output = cmd.output()?;
-                Ok(SuccessfulRun {
-                    out: output.stdout,
-                    err: output.stderr,
-                    code: output.status.code().unwrap_or(-1),
-                })
-            }
-            RunKind::Interactive => {
-                let status = cmd
-                    .stdin(Stdio::inherit())
-                    .stdout(Stdio::inherit())
-                    .stderr(Stdio::inherit())
-                    .status()?;
+        Ok(cmd)
+    }
+}
 
-                Ok(SuccessfulRun {
-                    out: vec![],
-                    err: vec![],
-                    code: status.code().unwrap_or(-1),
-                })
+impl Clause {
+    /// Reduces the clause to a single string. Bare symbols resolve
+    /// through the environment, literals are taken as written, and
+    /// embedded runnables become their trimmed output.
+    pub fn evaluate(self, ctx: &RunContext) -> io::Result<String> {
+        match self {
+            Clause::Literal(s) => Ok(s),
+            Clause::Bare(s) => Ok(ctx.env.resolve(&s)),
+            Clause::Embedded(runnable) => {
+                let result = runnable.run_in_context(ctx.as_embedded())?;
+                Ok(String::from_utf8_lossy(&result.out).trim().to_string())
             }
         }
     }
 }
 
+/// Names the program in errors from starting it, which the
+/// bare OS error doesn't do, and tags them with the exit status
+/// shells give a command that can't run - 127 when it isn't found,
+/// 126 otherwise
+pub fn spawn_error(cmd: &StdCommand, span: Span, e: io::Error) -> io::Error {
+    let program = cmd.get_program().to_string_lossy();
+
+    let (message, status) = match e.kind() {
+        io::ErrorKind::NotFound => (format!("command not found: {program}"), 127),
+        io::ErrorKind::PermissionDenied => (format!("permission denied: {program}"), 126),
+        _ => (e.to_string(), 126),
+    };
+
+    io::Error::new(
+        e.kind(),
+        LocatedError {
+            message,
+            span,
+            status: Some(status),
+        },
+    )
+}
+
+/// The first word of `words` is the program - the remaining
+/// words become leading args. This is how both bindings and
+/// embedded output in command position are expanded.
+fn split_program(words: &str, argv: &mut Vec<Clause>) -> io::Result<String> {
+    let mut parts = words.split_whitespace();
+
+    let program = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No command specified"))?;
+
+    argv.splice(0..0, parts.map(|s| Clause::Literal(s.to_string())));
+
+    Ok(program.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,6 +188,7 @@ mod tests {
     fn embedded_ctx() -> RunContext {
         RunContext {
             kind: RunKind::Embedded,
+            ..Default::default()
         }
     }
 
@@ -111,7 +197,7 @@ mod tests {
         let cmd = Executable::new();
         let result = cmd.execute(embedded_ctx());
 
-        let SuccessfulRun { out, err, code } = result.expect("Expected Ok for empty command");
+        let SuccessfulRun { out, err, code, .. } = result.expect("Expected Ok for empty command");
         assert_eq!(out, Vec::<u8>::new());
         assert_eq!(err, Vec::<u8>::new());
         assert_eq!(code, 0);
@@ -177,4 +263,73 @@ mod tests {
         assert!(output.contains("two"));
         assert!(output.contains("three"));
     }
+
+    #[test]
+    fn test_bound_program_expands_to_its_words() {
+        let ctx = embedded_ctx();
+        ctx.env.define("greet", "echo hello");
+
+        let mut cmd = Executable::new();
+        cmd.argv.push(Clause::Bare("greet".to_string()));
+        cmd.argv.push(Clause::Bare("world".to_string()));
+
+        let SuccessfulRun { out, .. } = cmd.execute(ctx).unwrap();
+        assert_eq!(String::from_utf8_lossy(&out), "hello world\n");
+    }
+
+    #[test]
+    fn test_bound_argument_resolves() {
+        let ctx = embedded_ctx();
+        ctx.env.define("who", "the world");
+
+        let mut cmd = Executable::new();
+        cmd.argv.push(Clause::Bare("printf".to_string()));
+        cmd.argv.push(Clause::Literal("[%s]".to_string()));
+        cmd.argv.push(Clause::Bare("who".to_string()));
+
+        let SuccessfulRun { out, .. } = cmd.execute(ctx).unwrap();
+        assert_eq!(String::from_utf8_lossy(&out), "[the world]");
+    }
+
+    #[test]
+    fn test_literal_skips_binding_lookup() {
+        let ctx = embedded_ctx();
+        ctx.env.define("echo", "nonexistent_command_xyz");
+        ctx.env.define("who", "bound");
+
+        let mut cmd = Executable::new();
+        cmd.argv.push(Clause::Literal("echo".to_string()));
+        cmd.argv.push(Clause::Literal("who".to_string()));
+
+        let SuccessfulRun { out, .. } = cmd.execute(ctx).unwrap();
+        assert_eq!(String::from_utf8_lossy(&out), "who\n");
+    }
+
+    #[test]
+    fn test_binding_runnable_defines_in_context() {
+        let ctx = embedded_ctx();
+        let binding = Runnable::Binding {
+            name: "x".to_string(),
+            value: Box::new(Clause::Bare("value".to_string())),
+        };
+
+        binding.run_in_context(ctx.clone()).unwrap();
+        assert_eq!(ctx.env.lookup("x"), Some("value".to_string()));
+    }
+
+    #[test]
+    fn test_binding_embedded_value_is_trimmed_output() {
+        let ctx = embedded_ctx();
+        let mut echo = Executable::new();
+        echo.argv.push(Clause::Literal("echo".to_string()));
+        echo.argv.push(Clause::Literal("captured".to_string()));
+
+        let binding = Runnable::Binding {
+            name: "x".to_string(),
+            value: Box::new(Clause::Embedded(Runnable::Command { command: echo })),
+        };
+
+        binding.run_in_context(ctx.clone()).unwrap();
+        assert_eq!(ctx.env.lookup("x"), Some("captured".to_string()));
+    }
 }
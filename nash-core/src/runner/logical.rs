@@ -0,0 +1,196 @@
+use crate::runner::{RunContext, Runnable, SuccessfulRun};
+use std::io;
+
+/// `and`, `or` and `not`, which combine runnables by their exit
+/// codes. A code of 0 is true, anything else false.
+#[derive(Debug)]
+pub enum Logical {
+    /// Runs operands until one fails
+    And(Vec<Runnable>),
+    /// Runs operands until one succeeds
+    Or(Vec<Runnable>),
+    /// Runs its operand and inverts the result
+    Not(Box<Runnable>),
+}
+
+impl Logical {
+    pub fn execute(self, ctx: RunContext) -> io::Result<SuccessfulRun> {
+        match self {
+            Logical::And(operands) => short_circuit(operands, ctx, |code| code != 0, 0),
+            Logical::Or(operands) => short_circuit(operands, ctx, |code| code == 0, 1),
+            Logical::Not(operand) => {
+                let mut result = operand.run_for_status(ctx)?;
+                result.code = if result.code == 0 { 1 } else { 0 };
+                Ok(result)
+            }
+        }
+    }
+}
+
+/// Runs `operands` in order until `stop` returns true for an exit
+/// code. Output of every operand that ran is kept, and the code is
+/// that of the last one - or `empty` when there are no operands.
+fn short_circuit(
+    operands: Vec<Runnable>,
+    ctx: RunContext,
+    stop: impl Fn(i32) -> bool,
+    empty: i32,
+) -> io::Result<SuccessfulRun> {
+    let mut combined = SuccessfulRun::new(vec![], vec![], empty);
+
+    for operand in operands {
+        let result = operand.run_for_status(ctx.clone())?;
+
+        combined.out.extend(result.out);
+        combined.err.extend(result.err);
+        combined.code = result.code;
+        combined.stage_codes = result.stage_codes;
+
+        if stop(combined.code) {
+            break;
+        }
+    }
+
+    Ok(combined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{
+        RunKind,
+        executable::{Clause, Executable},
+    };
+
+    fn embedded_ctx() -> RunContext {
+        RunContext {
+            kind: RunKind::Embedded,
+            ..Default::default()
+        }
+    }
+
+    fn command(argv: &[&str]) -> Runnable {
+        Runnable::Command {
+            command: Executable {
+                argv: argv
+                    .iter()
+                    .map(|s| Clause::Literal(s.to_string()))
+                    .collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn run(logical: Logical) -> SuccessfulRun {
+        logical.execute(embedded_ctx()).unwrap()
+    }
+
+    #[test]
+    fn test_and_runs_everything_on_success() {
+        let result = run(Logical::And(vec![
+            command(&["echo", "one"]),
+            command(&["echo", "two"]),
+        ]));
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "one\ntwo\n");
+        assert_eq!(result.code, 0);
+    }
+
+    #[test]
+    fn test_and_stops_at_first_failure() {
+        let result = run(Logical::And(vec![
+            command(&["echo", "one"]),
+            command(&["sh", "-c", "exit 4"]),
+            command(&["echo", "never"]),
+        ]));
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "one\n");
+        assert_eq!(result.code, 4);
+    }
+
+    #[test]
+    fn test_or_stops_at_first_success() {
+        let result = run(Logical::Or(vec![
+            command(&["false"]),
+            command(&["echo", "fallback"]),
+            command(&["echo", "never"]),
+        ]));
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "fallback\n");
+        assert_eq!(result.code, 0);
+    }
+
+    #[test]
+    fn test_or_fails_when_everything_fails() {
+        let result = run(Logical::Or(vec![
+            command(&["false"]),
+            command(&["sh", "-c", "exit 2"]),
+        ]));
+
+        assert_eq!(result.code, 2);
+    }
+
+    #[test]
+    fn test_empty_and_or() {
+        assert_eq!(run(Logical::And(vec![])).code, 0);
+        assert_eq!(run(Logical::Or(vec![])).code, 1);
+    }
+
+    #[test]
+    fn test_not_inverts() {
+        assert_eq!(run(Logical::Not(Box::new(command(&["true"])))).code, 1);
+        assert_eq!(run(Logical::Not(Box::new(command(&["false"])))).code, 0);
+    }
+
+    #[test]
+    fn test_or_falls_back_past_missing_command() {
+        let result = run(Logical::Or(vec![
+            command(&["nonexistent_command_xyz"]),
+            command(&["echo", "fallback"]),
+        ]));
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "fallback\n");
+        assert_eq!(
+            String::from_utf8_lossy(&result.err),
+            concat!(
+                "error: command not found: nonexistent_command_xyz\n",
+                " --> <input>:1:1\n",
+                "  |\n",
+                "1 | \n",
+                "  | ^ not found\n",
+            )
+        );
+        assert_eq!(result.code, 0);
+    }
+
+    #[test]
+    fn test_and_stops_at_missing_command() {
+        let result = run(Logical::And(vec![
+            command(&["nonexistent_command_xyz"]),
+            command(&["echo", "never"]),
+        ]));
+
+        assert!(result.out.is_empty());
+        assert_eq!(result.code, 127);
+    }
+
+    #[test]
+    fn test_not_of_missing_command_succeeds() {
+        let result = run(Logical::Not(Box::new(command(&[
+            "nonexistent_command_xyz",
+        ]))));
+        assert_eq!(result.code, 0);
+    }
+
+    #[test]
+    fn test_nested_logical() {
+        let result = run(Logical::And(vec![
+            Runnable::Logical {
+                logical: Logical::Not(Box::new(command(&["false"]))),
+            },
+            command(&["echo", "reached"]),
+        ]));
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "reached\n");
+    }
+}
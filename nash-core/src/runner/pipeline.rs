@@ -0,0 +1,223 @@
+use crate::runner::{RunContext, RunKind, Runnable, SuccessfulRun};
+use std::{
+    io::{self, Read},
+    process::{Child, ChildStderr, ChildStdout, Stdio},
+    thread,
+};
+
+/// A chain of commands, each stage's stdout connected to
+/// the next stage's stdin with an OS pipe.
+#[derive(Debug, Default)]
+pub struct Pipeline {
+    pub stages: Vec<Runnable>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline { stages: vec![] }
+    }
+
+    pub fn execute(self, ctx: RunContext) -> io::Result<SuccessfulRun> {
+        if self.stages.is_empty() {
+            return Ok(SuccessfulRun::new(vec![], vec![], 0));
+        }
+
+        let mut children = self.spawn_all(&ctx)?;
+        let last = children.len() - 1;
+
+        let mut out = vec![];
+        let mut err = vec![];
+
+        // Stderr of every stage is drained on its own thread so a chatty
+        // stage can't fill its pipe and stall the whole pipeline
+        thread::scope(|s| -> io::Result<()> {
+            let readers: Vec<_> = children
+                .iter_mut()
+                .map(|child| {
+                    let stderr = child.stderr.take();
+                    s.spawn(move || read_stderr(stderr))
+                })
+                .collect();
+
+            if let Some(mut stdout) = children[last].stdout.take() {
+                stdout.read_to_end(&mut out)?;
+            }
+
+            for reader in readers {
+                let stage_err = reader
+                    .join()
+                    .map_err(|_| io::Error::other("stderr reader panicked"))??;
+                err.extend(stage_err);
+            }
+
+            Ok(())
+        })?;
+
+        let stage_codes = children
+            .iter_mut()
+            .map(|child| child.wait().map(|status| status.code().unwrap_or(-1)))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(SuccessfulRun {
+            out,
+            err,
+            code: stage_codes[last],
+            stage_codes,
+        })
+    }
+
+    fn spawn_all(self, ctx: &RunContext) -> io::Result<Vec<Child>> {
+        let last = self.stages.len() - 1;
+        let mut children: Vec<Child> = Vec::with_capacity(self.stages.len());
+        let mut upstream: Option<ChildStdout> = None;
+
+        // Redirections around the whole pipeline only apply to its ends
+        let stage_ctx = ctx.without_redirects();
+
+        for (i, stage) in self.stages.into_iter().enumerate() {
+            let stdin = match (upstream.take(), &ctx.kind) {
+                (Some(pipe), _) => Stdio::from(pipe),
+                // the previous stage sent its stdout elsewhere
+                (None, _) if i > 0 => Stdio::null(),
+                (None, RunKind::Interactive) => ctx.stdin_or(Stdio::inherit())?,
+                (None, RunKind::Embedded) => ctx.stdin_or(Stdio::null())?,
+            };
+
+            let stdout = match (i == last, &ctx.kind) {
+                (true, RunKind::Interactive) => ctx.stdout_or(Stdio::inherit())?,
+                (true, RunKind::Embedded) => ctx.stdout_or(Stdio::piped())?,
+                (false, _) => Stdio::piped(),
+            };
+
+            let stderr = match ctx.kind {
+                RunKind::Interactive => Stdio::inherit(),
+                RunKind::Embedded => Stdio::piped(),
+            };
+
+            match stage.spawn(&stage_ctx, stdin, stdout, stderr) {
+                Ok(mut child) => {
+                    if i != last {
+                        upstream = child.stdout.take();
+                    }
+                    children.push(child);
+                }
+                Err(e) => {
+                    // Don't leave the stages already started running unattended
+                    for mut child in children {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(children)
+    }
+}
+
+fn read_stderr(stderr: Option<ChildStderr>) -> io::Result<Vec<u8>> {
+    let mut buf = vec![];
+    if let Some(mut stderr) = stderr {
+        stderr.read_to_end(&mut buf)?;
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::executable::{Clause, Executable};
+
+    fn embedded_ctx() -> RunContext {
+        RunContext {
+            kind: RunKind::Embedded,
+            ..Default::default()
+        }
+    }
+
+    fn command(argv: &[&str]) -> Runnable {
+        Runnable::Command {
+            command: Executable {
+                argv: argv
+                    .iter()
+                    .map(|s| Clause::Literal(s.to_string()))
+                    .collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_empty_pipeline() {
+        let result = Pipeline::new()
+            .execute(embedded_ctx())
+            .expect("Expected Ok for empty pipeline");
+        assert!(result.out.is_empty());
+        assert_eq!(result.code, 0);
+    }
+
+    #[test]
+    fn test_single_stage() {
+        let pipeline = Pipeline {
+            stages: vec![command(&["echo", "hello"])],
+        };
+
+        let result = pipeline.execute(embedded_ctx()).unwrap();
+        assert_eq!(String::from_utf8_lossy(&result.out), "hello\n");
+        assert_eq!(result.stage_codes, vec![0]);
+    }
+
+    #[test]
+    fn test_output_flows_between_stages() {
+        let pipeline = Pipeline {
+            stages: vec![
+                command(&["printf", "b\\na\\nc\\n"]),
+                command(&["sort"]),
+                command(&["head", "-n", "2"]),
+            ],
+        };
+
+        let result = pipeline.execute(embedded_ctx()).unwrap();
+        assert_eq!(String::from_utf8_lossy(&result.out), "a\nb\n");
+        assert_eq!(result.code, 0);
+    }
+
+    #[test]
+    fn test_reports_each_stage_exit_code() {
+        let pipeline = Pipeline {
+            stages: vec![
+                command(&["sh", "-c", "exit 3"]),
+                command(&["cat"]),
+                command(&["sh", "-c", "cat; exit 5"]),
+            ],
+        };
+
+        let result = pipeline.execute(embedded_ctx()).unwrap();
+        assert_eq!(result.stage_codes, vec![3, 0, 5]);
+        assert_eq!(result.code, 5);
+    }
+
+    #[test]
+    fn test_collects_stderr_of_every_stage() {
+        let pipeline = Pipeline {
+            stages: vec![
+                command(&["sh", "-c", "echo one >&2"]),
+                command(&["sh", "-c", "echo two >&2"]),
+            ],
+        };
+
+        let result = pipeline.execute(embedded_ctx()).unwrap();
+        assert_eq!(String::from_utf8_lossy(&result.err), "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_missing_stage_is_an_error() {
+        let pipeline = Pipeline {
+            stages: vec![command(&["yes"]), command(&["nonexistent_command_xyz"])],
+        };
+
+        let err = pipeline.execute(embedded_ctx()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::runner::{RunContext, executable::Clause, locate};
+use nash_parser::span::Span;
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    rc::Rc,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedirectKind {
+    /// `in` / `<` - read stdin from the file
+    In,
+    /// `out` / `>` - write stdout to the file, truncating it
+    Out,
+    /// `append` / `>>` - write stdout to the end of the file
+    Append,
+}
+
+#[derive(Debug)]
+pub struct Redirect {
+    pub kind: RedirectKind,
+    pub target: Clause,
+    /// Where the target was written, for error reporting
+    pub span: Span,
+}
+
+impl Redirect {
+    /// Opens the target file and returns a context that routes
+    /// the matching stream to it.
+    pub fn apply(self, mut ctx: RunContext) -> io::Result<RunContext> {
+        let span = self.span;
+        let path = target_path(self.target, &ctx).map_err(|e| locate(e, span))?;
+
+        let opened = match self.kind {
+            RedirectKind::In => File::open(&path),
+            RedirectKind::Out => File::create(&path),
+            RedirectKind::Append => OpenOptions::new().create(true).append(true).open(&path),
+        };
+
+        let file = opened.map_err(|e| {
+            let action = match self.kind {
+                RedirectKind::In => "open",
+                RedirectKind::Out | RedirectKind::Append => "create",
+            };
+            let e = io::Error::new(e.kind(), format!("cannot {action} '{path}': {e}"));
+            locate(e, span)
+        })?;
+
+        match self.kind {
+            RedirectKind::In => ctx.stdin = Some(Rc::new(file)),
+            RedirectKind::Out | RedirectKind::Append => ctx.stdout = Some(Rc::new(file)),
+        }
+
+        Ok(ctx)
+    }
+}
+
+fn target_path(target: Clause, ctx: &RunContext) -> io::Result<String> {
+    let path = target.evaluate(ctx)?;
+
+    if path.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "redirect target evaluated to an empty path",
+        ));
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{RunKind, Runnable, executable::Executable, pipeline::Pipeline};
+    use std::{fs, path::Path};
+    use tempfile::tempdir;
+
+    fn embedded_ctx() -> RunContext {
+        RunContext {
+            kind: RunKind::Embedded,
+            ..Default::default()
+        }
+    }
+
+    fn command(argv: &[&str]) -> Runnable {
+        Runnable::Command {
+            command: Executable {
+                argv: argv
+                    .iter()
+                    .map(|s| Clause::Literal(s.to_string()))
+                    .collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn redirected(runnable: Runnable, kind: RedirectKind, path: &Path) -> Runnable {
+        Runnable::Redirect {
+            runnable: Box::new(runnable),
+            redirect: Box::new(Redirect {
+                kind,
+                target: Clause::Literal(path.to_string_lossy().into_owned()),
+                span: Span::default(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_out_writes_file_instead_of_capturing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("out");
+        fs::write(&path, "stale contents\n").unwrap();
+
+        let result = redirected(command(&["echo", "hello"]), RedirectKind::Out, &path)
+            .run_in_context(embedded_ctx())
+            .unwrap();
+
+        assert!(result.out.is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello\n");
+    }
+
+    #[test]
+    fn test_append_keeps_existing_contents() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("append");
+        fs::write(&path, "one\n").unwrap();
+
+        redirected(command(&["echo", "two"]), RedirectKind::Append, &path)
+            .run_in_context(embedded_ctx())
+            .unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_in_feeds_file_to_stdin() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("in");
+        fs::write(&path, "from a file\n").unwrap();
+
+        let result = redirected(command(&["cat"]), RedirectKind::In, &path)
+            .run_in_context(embedded_ctx())
+            .unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&result.out), "from a file\n");
+    }
+
+    #[test]
+    fn test_missing_input_file_names_the_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("missing");
+
+        let err = redirected(command(&["cat"]), RedirectKind::In, &path)
+            .run_in_context(embedded_ctx())
+            .err()
+            .unwrap();
+
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains(&*path.to_string_lossy()));
+    }
+
+    #[test]
+    fn test_uncreatable_output_file_is_an_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("no-such-dir").join("out.txt");
+
+        let err = redirected(command(&["echo"]), RedirectKind::Out, &path)
+            .run_in_context(embedded_ctx())
+            .err()
+            .unwrap();
+
+        assert!(err.to_string().starts_with("cannot create"));
+    }
+
+    #[test]
+    fn test_redirected_stage_inside_pipeline() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("stage-in");
+        fs::write(&input, "b\na\n").unwrap();
+
+        let pipeline = Pipeline {
+            stages: vec![
+                redirected(command(&["cat"]), RedirectKind::In, &input),
+                command(&["sort"]),
+            ],
+        };
+
+        let result = pipeline.execute(embedded_ctx()).unwrap();
+        assert_eq!(String::from_utf8_lossy(&result.out), "a\nb\n");
+    }
+
+    #[test]
+    fn test_redirected_pipeline_writes_last_stage() {
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("pipeline-out");
+
+        let pipeline = Runnable::Pipeline {
+            pipeline: Pipeline {
+                stages: vec![command(&["echo", "piped"]), command(&["cat"])],
+            },
+        };
+
+        let result = redirected(pipeline, RedirectKind::Out, &output)
+            .run_in_context(embedded_ctx())
+            .unwrap();
+
+        assert!(result.out.is_empty());
+        assert_eq!(fs::read_to_string(&output).unwrap(), "piped\n");
+    }
+}
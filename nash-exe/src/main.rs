@@ -1,17 +1,254 @@
-use nash_core::runner;
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Read},
+    process::ExitCode,
+    rc::Rc,
+};
+
+use nash_core::runner::{self, RunContext};
 use nash_line::editor::{NashEditor, Signal};
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut ed: NashEditor = Default::default();
+const USAGE: &str = "usage: nash [--vi | -c command [name [arg ...]] | script [arg ...]]";
+
+/// What the command line asked for
+#[derive(Debug, PartialEq)]
+enum Invocation {
+    /// The line editor, in vi mode if asked for, or a script on
+    /// stdin when it isn't a terminal
+    Interactive { vi: bool },
+    /// `-c`, with `args[0]` naming the script
+    Command { command: String, args: Vec<String> },
+    /// A script file, with `args[0]` its path
+    Script { args: Vec<String> },
+}
+
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let invocation = match parse_args(env::args().skip(1)) {
+        Ok(invocation) => invocation,
+        Err(message) => {
+            eprintln!("nash: {message}\n{USAGE}");
+            return Ok(ExitCode::from(2));
+        }
+    };
+
+    let status = match invocation {
+        Invocation::Interactive { vi } if io::stdin().is_terminal() => repl(vi)?,
+        Invocation::Interactive { .. } => {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source)?;
+            run_script("<stdin>", &source, &["nash".to_string()])
+        }
+        Invocation::Command { command, args } => run_script("<command>", &command, &args),
+        Invocation::Script { args } => {
+            let path = &args[0];
+            match fs::read_to_string(path) {
+                Ok(source) => run_script(path, &source, &args),
+                Err(e) => {
+                    eprintln!("nash: {path}: {e}");
+                    if e.kind() == io::ErrorKind::NotFound {
+                        127
+                    } else {
+                        126
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(ExitCode::from(status as u8))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Invocation, String> {
+    let Some(first) = args.next() else {
+        return Ok(Invocation::Interactive { vi: false });
+    };
+
+    match first.as_str() {
+        "-c" => {
+            let command = args.next().ok_or("-c requires an argument")?;
+            let mut args: Vec<String> = args.collect();
+            if args.is_empty() {
+                args.push("nash".to_string());
+            }
+            Ok(Invocation::Command { command, args })
+        }
+        "--vi" => match args.next() {
+            Some(arg) => Err(format!("--vi starts the editor, not {arg}")),
+            None => Ok(Invocation::Interactive { vi: true }),
+        },
+        "--" => match args.next() {
+            Some(path) => Ok(Invocation::Script {
+                args: std::iter::once(path).chain(args).collect(),
+            }),
+            None => Ok(Invocation::Interactive { vi: false }),
+        },
+        flag if flag.starts_with('-') => Err(format!("unknown option: {flag}")),
+        _ => Ok(Invocation::Script {
+            args: std::iter::once(first).chain(args).collect(),
+        }),
+    }
+}
+
+/// Runs a whole script, stopping at the first error. `args` are
+/// bound as `$0`, `$1` and so on, with their count in `$#`.
+fn run_script(origin: &str, source: &str, args: &[String]) -> i32 {
+    let ctx = RunContext::default();
+    for (i, arg) in args.iter().enumerate() {
+        ctx.env.define(format!("${i}"), arg.as_str());
+    }
+    ctx.env.define("$#", (args.len() - 1).to_string());
+
+    // The script's own `def`s shadow its arguments rather than
+    // overwriting them
+    let ctx = RunContext {
+        env: ctx.env.child(),
+        origin: Rc::from(origin),
+        ..ctx
+    };
+
+    let source = strip_shebang(source);
+    match runner::run_in_context(&source, &ctx) {
+        Ok(code) => code,
+        Err(e) => {
+            eprint!("{}", e.report(origin, &source));
+            e.exit_code()
+        }
+    }
+}
+
+/// Turns a leading `#!` line into a comment. Offsets are unchanged,
+/// so errors still point at the right place in the file.
+fn strip_shebang(source: &str) -> String {
+    match source.strip_prefix("#!") {
+        Some(rest) => format!(";!{rest}"),
+        None => source.to_string(),
+    }
+}
 
+/// Runs lines from the editor until it's closed. `NASH_EDIT_MODE=vi`
+/// also asks for vi mode, for setting it once in a profile.
+fn repl(vi: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    let ctx = RunContext::default();
+    let env = ctx.env.clone();
+    let mut ed = NashEditor::with_bindings(move || env.names());
+    if vi || env::var("NASH_EDIT_MODE").is_ok_and(|mode| mode == "vi") {
+        ed = ed.with_vi_mode();
+    }
+    let mut last_status = 0;
+
+    // Only the editor failing ends the session - anything wrong
+    // with a line is reported and the loop carries on
     loop {
         let line = ed.read_line()?;
 
         match line {
-            Signal::Complete(l) => runner::run(&l)?,
+            Signal::Complete(l) => {
+                last_status = match runner::run_in_context(&l, &ctx) {
+                    Ok(code) => code,
+                    Err(e) => {
+                        eprint!("{}", e.report("<input>", &l));
+                        e.exit_code()
+                    }
+                }
+            }
             Signal::Aborted => break,
         }
     }
 
-    Ok(())
+    Ok(last_status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Invocation, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_args_is_interactive() {
+        assert_eq!(parse(&[]), Ok(Invocation::Interactive { vi: false }));
+    }
+
+    #[test]
+    fn test_vi_flag() {
+        assert_eq!(parse(&["--vi"]), Ok(Invocation::Interactive { vi: true }));
+        assert!(parse(&["--vi", "build.nash"]).is_err());
+        assert_eq!(
+            parse(&["--", "--vi"]),
+            Ok(Invocation::Script {
+                args: strings(&["--vi"])
+            })
+        );
+    }
+
+    #[test]
+    fn test_command_mode() {
+        assert_eq!(
+            parse(&["-c", "(ls)"]),
+            Ok(Invocation::Command {
+                command: "(ls)".to_string(),
+                args: strings(&["nash"])
+            })
+        );
+        assert_eq!(
+            parse(&["-c", "(echo $1)", "name", "one"]),
+            Ok(Invocation::Command {
+                command: "(echo $1)".to_string(),
+                args: strings(&["name", "one"])
+            })
+        );
+        assert!(parse(&["-c"]).is_err());
+    }
+
+    #[test]
+    fn test_script_takes_remaining_args() {
+        assert_eq!(
+            parse(&["build.nash", "-c", "x"]),
+            Ok(Invocation::Script {
+                args: strings(&["build.nash", "-c", "x"])
+            })
+        );
+        assert_eq!(
+            parse(&["--", "-odd.nash"]),
+            Ok(Invocation::Script {
+                args: strings(&["-odd.nash"])
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_option() {
+        assert!(parse(&["-x"]).is_err());
+    }
+
+    #[test]
+    fn test_shebang_becomes_comment() {
+        let source = "#!/usr/bin/env nash\n(ls)";
+        let stripped = strip_shebang(source);
+        assert_eq!(stripped.len(), source.len());
+        assert_eq!(nash_parser::parser::parse(&stripped).unwrap().len(), 1);
+        assert_eq!(strip_shebang("(ls)"), "(ls)");
+    }
+
+    #[test]
+    fn test_script_exit_status() {
+        assert_eq!(
+            run_script("<test>", "(true) (sh -c 'exit 3')", &strings(&["t"])),
+            3
+        );
+        assert_eq!(run_script("<test>", "(oops", &strings(&["t"])), 2);
+    }
+
+    #[test]
+    fn test_positional_bindings() {
+        let script = "(test $# = 2) (test $1 = one) (test $2 = 'two words')";
+        let args = strings(&["t", "one", "two words"]);
+        assert_eq!(run_script("<test>", script, &args), 0);
+    }
 }
@@ -0,0 +1,50 @@
+use super::{Completer, Completion, Kind, starting_with};
+use nash_parser::partial::Word;
+
+/// Completes the names the shell has bound with `def`.
+///
+/// The names are asked for on every completion, from whatever owns
+/// them - the line editor can't depend on the shell that runs its
+/// lines, so the shell hands over a way to list them instead.
+pub struct BindingCompleter {
+    names: Box<dyn Fn() -> Vec<String>>,
+}
+
+impl BindingCompleter {
+    pub fn new(names: impl Fn() -> Vec<String> + 'static) -> Self {
+        Self {
+            names: Box::new(names),
+        }
+    }
+}
+
+impl Completer for BindingCompleter {
+    fn complete(&self, word: &Word) -> Vec<Completion> {
+        let names = (self.names)();
+        starting_with(Kind::Binding, word, names.iter().map(String::as_str))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nash_parser::partial::context_at;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_sees_bindings_made_later() {
+        let bound = Rc::new(RefCell::new(vec!["build".to_string()]));
+        let completer = BindingCompleter::new({
+            let bound = Rc::clone(&bound);
+            move || bound.borrow().clone()
+        });
+        let word = context_at("(b", 2).word;
+
+        assert_eq!(completer.complete(&word).len(), 1);
+        bound.borrow_mut().push("bump".to_string());
+
+        let completions = completer.complete(&word);
+        assert_eq!(completions.len(), 2);
+        assert!(completions.iter().all(|c| c.kind == Kind::Binding));
+    }
+}
@@ -0,0 +1,35 @@
+use super::{Completer, Completion, Kind, starting_with};
+use nash_parser::{forms::Form, partial::Word};
+
+/// Completes the special forms, by every operator that invokes them
+pub struct BuiltinCompleter;
+
+impl Completer for BuiltinCompleter {
+    fn complete(&self, word: &Word) -> Vec<Completion> {
+        let operators = Form::ALL.iter().flat_map(|form| form.operators());
+        starting_with(Kind::Builtin, word, operators.copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nash_parser::partial::context_at;
+
+    fn complete(s: &str) -> Vec<String> {
+        let word = context_at(s, s.len()).word;
+        BuiltinCompleter
+            .complete(&word)
+            .into_iter()
+            .map(|c| c.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_completes_forms() {
+        assert_eq!(complete("(pi"), vec!["pipe"]);
+        assert_eq!(complete("(a"), vec!["and", "append"]);
+        assert_eq!(complete("(>"), vec![">", ">>"]);
+        assert!(complete("(ls").is_empty());
+    }
+}
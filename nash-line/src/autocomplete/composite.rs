@@ -0,0 +1,135 @@
+use super::{Completer, Completion};
+use nash_parser::partial::Word;
+
+/// Merges the completions of several completers into one list.
+///
+/// Candidates that complete the word outright come first, then
+/// those that only match it fuzzily. Otherwise sources keep the
+/// order they were added in, and each keeps its own ranking. When
+/// two sources offer the same text the earlier one wins.
+#[derive(Default)]
+pub struct CompositeCompleter {
+    sources: Vec<Box<dyn Completer>>,
+}
+
+impl CompositeCompleter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, source: impl Completer + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+}
+
+impl Completer for CompositeCompleter {
+    fn complete(&self, word: &Word) -> Vec<Completion> {
+        let mut merged: Vec<Completion> = vec![];
+
+        for source in &self.sources {
+            for completion in source.complete(word) {
+                let seen = merged
+                    .iter()
+                    .any(|c| c.text == completion.text && c.span == completion.span);
+                if !seen {
+                    merged.push(completion);
+                }
+            }
+        }
+
+        let prefix = word.text.to_lowercase();
+        merged.sort_by_key(|c| {
+            let completed = c.apply(&word.text);
+            (
+                completed != word.text,
+                !completed.to_lowercase().starts_with(&prefix),
+            )
+        });
+
+        merged
+    }
+
+    /// Every source is told, and those that didn't offer it
+    /// have nothing to rank
+    fn accepted(&mut self, completion: &Completion) {
+        for source in &mut self.sources {
+            source.accepted(completion);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autocomplete::Kind;
+    use nash_parser::partial::context_at;
+
+    /// Offers `names` whatever the word, like a fuzzy matcher might
+    struct Fixed(Kind, Vec<&'static str>);
+
+    impl Completer for Fixed {
+        fn complete(&self, word: &Word) -> Vec<Completion> {
+            self.1
+                .iter()
+                .map(|name| Completion::word(self.0, word, *name))
+                .collect()
+        }
+    }
+
+    fn complete(completer: &CompositeCompleter, s: &str) -> Vec<(String, Kind)> {
+        let word = context_at(s, s.len()).word;
+        completer
+            .complete(&word)
+            .into_iter()
+            .map(|c| (c.text, c.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_sources_are_merged_in_order() {
+        let completer = CompositeCompleter::new()
+            .with(Fixed(Kind::Builtin, vec!["pipe"]))
+            .with(Fixed(Kind::Executable, vec!["pip", "pinky"]));
+
+        assert_eq!(
+            complete(&completer, "(pi"),
+            vec![
+                ("pipe".to_string(), Kind::Builtin),
+                ("pip".to_string(), Kind::Executable),
+                ("pinky".to_string(), Kind::Executable),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exact_then_prefix_then_fuzzy() {
+        let completer = CompositeCompleter::new()
+            .with(Fixed(Kind::Binding, vec!["dc-up"]))
+            .with(Fixed(Kind::Executable, vec!["dcx", "dc"]));
+
+        let texts: Vec<String> = complete(&completer, "(dc")
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect();
+        assert_eq!(texts, vec!["dc", "dc-up", "dcx"]);
+
+        let texts: Vec<String> = complete(&completer, "(dc-")
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect();
+        assert_eq!(texts, vec!["dc-up", "dcx", "dc"]);
+    }
+
+    #[test]
+    fn test_earlier_source_wins_duplicates() {
+        let completer = CompositeCompleter::new()
+            .with(Fixed(Kind::Binding, vec!["ls"]))
+            .with(Fixed(Kind::Executable, vec!["ls"]));
+
+        assert_eq!(
+            complete(&completer, "(l"),
+            vec![("ls".to_string(), Kind::Binding)]
+        );
+    }
+}
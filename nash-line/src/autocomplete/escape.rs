@@ -0,0 +1,83 @@
+//! Writing completions back so the lexer reads them as they were
+//!
+//! Completers hand back text as it should be read, and these escape
+//! it for where it is going - a bare symbol, or the inside of a
+//! quoted literal.
+
+/// Chars that end a symbol unless escaped
+fn needs_escape(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\'')
+}
+
+/// `s` written as a symbol. Symbols have no escape for `;`, so
+/// anything holding one is quoted instead.
+pub fn escape(s: &str) -> String {
+    if s.contains(';') {
+        return format!("'{}'", escape_literal(s));
+    }
+
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if needs_escape(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `s` written inside a quoted literal, without the quotes. Either
+/// quote char is escaped, whichever quote the literal opened with.
+pub fn escape_literal(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '"' | '\'') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nash_parser::lexer::{Token, lex};
+
+    fn round_trips(s: &str) {
+        let tokens = lex(&escape(s)).unwrap();
+        match tokens.as_slice() {
+            [Token::Symbol(read) | Token::Literal(read)] => assert_eq!(read, s),
+            other => panic!("{s:?} lexed as {other:?}"),
+        }
+
+        let quoted = format!("\"{}\"", escape_literal(s));
+        assert_eq!(lex(&quoted).unwrap(), vec![Token::Literal(s.to_string())]);
+    }
+
+    #[test]
+    fn test_plain_names_are_unchanged() {
+        assert_eq!(escape("src/main.rs"), "src/main.rs");
+        round_trips("src/main.rs");
+    }
+
+    #[test]
+    fn test_specials_are_escaped() {
+        assert_eq!(escape("my file (1).txt"), "my\\ file\\ \\(1\\).txt");
+        round_trips("my file (1).txt");
+        round_trips("it's \"quoted\"");
+        round_trips("tab\there");
+    }
+
+    #[test]
+    fn test_semicolon_is_quoted() {
+        assert_eq!(escape("a;b"), "'a;b'");
+        round_trips("a;b");
+        round_trips("it's;odd");
+    }
+
+    #[test]
+    fn test_literal_escapes_only_quotes() {
+        assert_eq!(escape_literal("a (b) c's"), "a (b) c\\'s");
+    }
+}
@@ -1,68 +1,206 @@
-use super::{Completer, trie::Trie};
-use std::{os::unix::fs::PermissionsExt, vec};
+use super::{Completer, Completion, Kind, scan, trie::Trie};
+use nash_parser::partial::Word;
+use nash_path::Resolved;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    thread::{self, JoinHandle},
+};
 
 pub struct ExecutableCompleter {
+    executables: RefCell<Executables>,
+    /// A `PATH` scan still running on another thread, merged in
+    /// the first time completions are needed
+    scan: Cell<Option<JoinHandle<Vec<Resolved>>>>,
+}
+
+#[derive(Default)]
+struct Executables {
     completions: Trie,
+    /// Where each name resolves to
+    resolved: HashMap<String, Resolved>,
+}
+
+impl Executables {
+    fn extend(&mut self, found: Vec<Resolved>) {
+        for resolved in found {
+            self.completions.insert(&resolved.name);
+            self.resolved.insert(resolved.name.clone(), resolved);
+        }
+    }
+}
+
+impl Default for ExecutableCompleter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ExecutableCompleter {
     pub fn new() -> Self {
         Self {
-            completions: Trie::new(),
+            executables: RefCell::new(Executables::default()),
+            scan: Cell::new(None),
         }
     }
 
-    pub fn with_executables_in_path(mut self) -> Self {
-        let executables = find_executables();
+    pub fn with_executables(self, found: Vec<Resolved>) -> Self {
+        self.executables.borrow_mut().extend(found);
+        self
+    }
 
-        for exe in executables {
-            self.completions.insert(&exe);
-        }
+    /// Adds the executables on `PATH`, found through the scan cache
+    pub fn with_executables_in_path(self) -> Self {
+        let cache = scan::default_location();
+        self.with_executables(scan::executables_in_path(cache.as_deref()))
+    }
 
+    /// Like [`Self::with_executables_in_path`], but scans on another
+    /// thread so building the completer doesn't wait on the disk
+    pub fn scanning_path_in_background(self) -> Self {
+        let handle = thread::spawn(|| {
+            let cache = scan::default_location();
+            scan::executables_in_path(cache.as_deref())
+        });
+        self.scan.set(Some(handle));
         self
     }
+
+    /// Executables that hide others of the same name further along
+    /// `PATH`, by name
+    pub fn shadowed(&self) -> Vec<Resolved> {
+        self.finish_scan();
+        let mut shadowed: Vec<Resolved> = self
+            .executables
+            .borrow()
+            .resolved
+            .values()
+            .filter(|resolved| !resolved.shadowed.is_empty())
+            .cloned()
+            .collect();
+
+        shadowed.sort_by(|a, b| a.name.cmp(&b.name));
+        shadowed
+    }
+
+    /// Waits out any background scan and takes what it found
+    fn finish_scan(&self) {
+        // A scan that panicked just leaves nothing to complete
+        if let Some(handle) = self.scan.take()
+            && let Ok(found) = handle.join()
+        {
+            self.executables.borrow_mut().extend(found);
+        }
+    }
 }
 
 #[cfg(debug_assertions)]
 impl ExecutableCompleter {
-    pub fn trie(&self) -> &Trie {
-        &self.completions
+    pub fn trie(&self) -> std::cell::Ref<'_, Trie> {
+        self.finish_scan();
+        std::cell::Ref::map(self.executables.borrow(), |e| &e.completions)
     }
 }
 
 impl Completer for ExecutableCompleter {
-    fn complete(&self, s: &str) -> Vec<String> {
-        if let Some(completions) = self.completions.get_completions(s) {
-            return completions;
-        }
+    fn complete(&self, word: &Word) -> Vec<Completion> {
+        self.finish_scan();
+        let executables = self.executables.borrow();
+
+        let names = match executables.completions.get_completions(&word.text) {
+            Some(names) if !names.is_empty() => names,
+            // Nothing starts with the word, so try it as an abbreviation
+            _ => executables
+                .completions
+                .fuzzy_matches(&word.text)
+                .into_iter()
+                .map(|m| m.name)
+                .collect(),
+        };
 
-        vec![]
+        names
+            .into_iter()
+            .map(|name| {
+                let detail = executables.resolved.get(&name).map(describe);
+                let completion = Completion::word(Kind::Executable, word, name);
+                match detail {
+                    Some(detail) => completion.with_detail(detail),
+                    None => completion,
+                }
+            })
+            .collect()
+    }
+
+    fn accepted(&mut self, completion: &Completion) {
+        self.finish_scan();
+        self.executables
+            .get_mut()
+            .completions
+            .bump(&completion.text);
     }
 }
 
-fn find_executables() -> Vec<String> {
-    let path_var = std::env::var("PATH").unwrap_or_default();
-    let mut executables = Vec::new();
-
-    for dir in std::env::split_paths(&path_var) {
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() {
-                    let is_executable = entry
-                        .metadata()
-                        .map(|m| m.permissions().mode() & 0o111 != 0)
-                        .unwrap_or(false);
-
-                    if is_executable {
-                        if let Some(name) = path.file_name() {
-                            executables.push(name.to_string_lossy().into_owned());
-                        }
-                    }
-                }
-            }
+/// Where the name runs from, and what that hides
+fn describe(resolved: &Resolved) -> String {
+    let path = resolved.path.display();
+    match resolved.shadowed.as_slice() {
+        [] => path.to_string(),
+        shadowed => {
+            let shadowed: Vec<String> = shadowed.iter().map(|p| p.display().to_string()).collect();
+            format!("{path} (shadows {})", shadowed.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nash_parser::partial::context_at;
+    use std::path::PathBuf;
+
+    fn resolved(name: &str, path: &str, shadowed: &[&str]) -> Resolved {
+        Resolved {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+            shadowed: shadowed.iter().map(PathBuf::from).collect(),
         }
     }
 
-    executables
+    #[test]
+    fn test_completions_show_resolved_path() {
+        let completer = ExecutableCompleter::new().with_executables(vec![
+            resolved("python", "/usr/local/bin/python", &["/usr/bin/python"]),
+            resolved("pwd", "/bin/pwd", &[]),
+        ]);
+
+        let word = context_at("(p", 2).word;
+        let details: Vec<(String, Option<String>)> = completer
+            .complete(&word)
+            .into_iter()
+            .map(|c| (c.text, c.detail))
+            .collect();
+
+        assert_eq!(
+            details,
+            vec![
+                ("pwd".to_string(), Some("/bin/pwd".to_string())),
+                (
+                    "python".to_string(),
+                    Some("/usr/local/bin/python (shadows /usr/bin/python)".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_shadowed() {
+        let completer = ExecutableCompleter::new().with_executables(vec![
+            resolved("python", "/usr/local/bin/python", &["/usr/bin/python"]),
+            resolved("pwd", "/bin/pwd", &[]),
+        ]);
+
+        let shadowed = completer.shadowed();
+        assert_eq!(shadowed.len(), 1);
+        assert_eq!(shadowed[0].name, "python");
+    }
 }
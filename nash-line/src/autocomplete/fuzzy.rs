@@ -0,0 +1,152 @@
+//! Subsequence matching, fzf style
+//!
+//! A pattern matches a candidate when its chars appear in the
+//! candidate in order, ignoring case - `dkcmp` matches
+//! `docker-compose`. Matches are scored so the tightest and most
+//! word-aligned come first.
+
+/// Every matched char scores this much
+const MATCH: i32 = 16;
+/// Extra for a match right after the one before it
+const CONSECUTIVE: i32 = 8;
+/// Extra for a match at the start of a word, like the `c` in
+/// `docker-compose`
+const WORD_START: i32 = 8;
+/// Taken off for each char skipped between matches
+const GAP: i32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub name: String,
+    pub score: i32,
+    /// Char offsets into `name` of the matched chars
+    pub positions: Vec<usize>,
+}
+
+/// Scores `candidate` against `pattern`, or `None` when it doesn't
+/// match. The best scoring alignment is the one reported.
+pub fn score(pattern: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().map(norm).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            name: candidate.to_owned(),
+            score: 0,
+            positions: vec![],
+        });
+    }
+
+    // best[i][k] is the best score with pattern[..=i] matched and
+    // pattern[i] at chars[k], with where pattern[i - 1] went
+    let mut best: Vec<Vec<Option<(i32, usize)>>> = vec![vec![None; chars.len()]; pattern.len()];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        for k in 0..chars.len() {
+            if norm(chars[k]) != p {
+                continue;
+            }
+
+            let bonus = if is_word_start(&chars, k) {
+                WORD_START
+            } else {
+                0
+            };
+
+            if i == 0 {
+                best[0][k] = Some((MATCH + bonus - GAP * k as i32, k));
+                continue;
+            }
+
+            best[i][k] = (0..k)
+                .filter_map(|prev| {
+                    let (score, _) = best[i - 1][prev]?;
+                    let link = if prev + 1 == k {
+                        CONSECUTIVE
+                    } else {
+                        -GAP * (k - prev - 1) as i32
+                    };
+                    Some((score + MATCH + bonus + link, prev))
+                })
+                .max_by_key(|&(score, prev)| (score, prev));
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (end, (score, _)) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(k, cell)| Some((k, (*cell)?)))
+        .max_by_key(|&(k, (score, _))| (score, std::cmp::Reverse(k)))?;
+
+    // Walk back through where each earlier char went
+    let mut positions = vec![end];
+    let mut k = end;
+    for i in (1..=last).rev() {
+        let (_, prev) = best[i][k].expect("on the best path");
+        positions.push(prev);
+        k = prev;
+    }
+    positions.reverse();
+
+    Some(FuzzyMatch {
+        name: candidate.to_owned(),
+        score,
+        positions,
+    })
+}
+
+fn is_word_start(chars: &[char], k: usize) -> bool {
+    match k.checked_sub(1).map(|i| chars[i]) {
+        None => true,
+        Some(prev) => {
+            matches!(prev, '-' | '_' | '.' | '/' | ' ')
+                || (prev.is_lowercase() && chars[k].is_uppercase())
+        }
+    }
+}
+
+fn norm(c: char) -> char {
+    c.to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence_matches() {
+        let m = score("dkcmp", "docker-compose").unwrap();
+        assert_eq!(m.positions, vec![0, 3, 7, 9, 10]);
+    }
+
+    #[test]
+    fn test_out_of_order_does_not_match() {
+        assert_eq!(score("pmc", "docker-compose"), None);
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let m = score("NM", "NetworkManager").unwrap();
+        assert_eq!(m.positions, vec![0, 7]);
+    }
+
+    #[test]
+    fn test_consecutive_beats_scattered() {
+        let tight = score("comp", "compose").unwrap();
+        let loose = score("comp", "cat-o-mp").unwrap();
+        assert!(tight.score > loose.score);
+    }
+
+    #[test]
+    fn test_word_starts_preferred() {
+        // `c` could match in `docker`, but the word start scores better
+        let m = score("dc", "docker-compose").unwrap();
+        assert_eq!(m.positions, vec![0, 7]);
+    }
+
+    #[test]
+    fn test_empty_pattern_matches_everything() {
+        assert_eq!(score("", "ls").unwrap().positions, Vec::<usize>::new());
+    }
+}
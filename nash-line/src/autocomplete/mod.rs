@@ -1,25 +1,128 @@
-use crate::autocomplete::{
-    executable::ExecutableCompleter,
-    parser::{TokenType, determine_token_type},
-};
-
+pub mod binding;
+pub mod builtin;
+pub mod composite;
+pub mod escape;
 pub mod executable;
-mod parser;
+pub mod fuzzy;
+pub mod path;
+pub mod scan;
+pub mod slot;
 pub mod trie;
 
+use nash_parser::partial::Word;
+use std::ops::Range;
+
 pub trait Completer {
-    fn complete(&self, s: &str) -> Vec<String>;
+    /// Candidates for the word at the cursor
+    fn complete(&self, word: &Word) -> Vec<Completion>;
+
+    /// Told which completion the user went with, so it can be
+    /// ranked higher next time
+    fn accepted(&mut self, _completion: &Completion) {}
+}
+
+/// Completes nothing, for editors built without a completer
+pub struct NoCompleter;
+
+impl Completer for NoCompleter {
+    fn complete(&self, _word: &Word) -> Vec<Completion> {
+        vec![]
+    }
+}
+
+/// A candidate for part of a word. Spans index the word's text as
+/// the lexer reads it, so completers never see quotes or escapes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    /// What goes in place of `span`
+    pub text: String,
+    /// The bytes of the word's text that `text` replaces
+    pub span: Range<usize>,
+    pub kind: Kind,
+    /// More about the candidate for the menu, like where an
+    /// executable lives
+    pub detail: Option<String>,
 }
 
-pub fn autocomplete(buffer_fragment: &str) -> Vec<String> {
-    let completer = ExecutableCompleter::new().with_executables_in_path();
+/// What a completion names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// A special form, like `pipe` or `def`
+    Builtin,
+    /// A name bound with `def`
+    Binding,
+    /// A program on `PATH`
+    Executable,
+    /// A file or directory
+    Path,
+}
 
-    match determine_token_type(buffer_fragment) {
-        TokenType::Symbol(s) => {
-            return completer.complete(&s);
+impl Kind {
+    /// How the menu labels it
+    pub fn tag(self) -> &'static str {
+        match self {
+            Kind::Builtin => "builtin",
+            Kind::Binding => "binding",
+            Kind::Executable => "executable",
+            Kind::Path => "path",
         }
-        _ => {
-            return vec![];
+    }
+}
+
+impl Completion {
+    /// Replaces the whole word
+    pub fn word(kind: Kind, word: &Word, text: impl Into<String>) -> Self {
+        Completion {
+            text: text.into(),
+            span: 0..word.text.len(),
+            kind,
+            detail: None,
         }
     }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// `word` with the completion in place
+    pub fn apply(&self, word: &str) -> String {
+        format!(
+            "{}{}{}",
+            &word[..self.span.start],
+            self.text,
+            &word[self.span.end..]
+        )
+    }
+}
+
+/// Completions of `word` from `names`, for the ones that start
+/// with it ignoring case
+fn starting_with<'a>(
+    kind: Kind,
+    word: &Word,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Vec<Completion> {
+    let prefix = word.text.to_lowercase();
+    names
+        .into_iter()
+        .filter(|name| name.to_lowercase().starts_with(&prefix))
+        .map(|name| Completion::word(kind, word, name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_replaces_its_span() {
+        let completion = Completion {
+            text: "main.rs".to_string(),
+            span: 4..6,
+            kind: Kind::Path,
+            detail: None,
+        };
+        assert_eq!(completion.apply("src/ma"), "src/main.rs");
+    }
 }
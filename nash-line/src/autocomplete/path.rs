@@ -0,0 +1,200 @@
+use super::{Completer, Completion, Kind};
+use nash_parser::partial::Word;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Completes file and directory paths - relative, absolute, or
+/// starting from `~`. Directories get a trailing `/`. Only the name
+/// after the last `/` is replaced.
+pub struct PathCompleter {
+    /// What relative paths are relative to, or the working
+    /// directory when `None`
+    base: Option<PathBuf>,
+    home: Option<PathBuf>,
+}
+
+impl Default for PathCompleter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathCompleter {
+    pub fn new() -> Self {
+        Self {
+            base: None,
+            home: std::env::var_os("HOME").map(PathBuf::from),
+        }
+    }
+
+    pub fn relative_to(mut self, base: impl Into<PathBuf>) -> Self {
+        self.base = Some(base.into());
+        self
+    }
+
+    pub fn with_home(mut self, home: impl Into<PathBuf>) -> Self {
+        self.home = Some(home.into());
+        self
+    }
+
+    /// Where the directory part of a completion really is
+    fn resolve(&self, dir: &str) -> Option<PathBuf> {
+        let expanded = match dir.strip_prefix('~') {
+            Some(rest) => self.home.as_ref()?.join(rest.trim_start_matches('/')),
+            None => PathBuf::from(dir),
+        };
+
+        if expanded.is_absolute() {
+            return Some(expanded);
+        }
+
+        Some(match &self.base {
+            Some(base) => base.join(expanded),
+            None => expanded,
+        })
+    }
+}
+
+impl Completer for PathCompleter {
+    fn complete(&self, word: &Word) -> Vec<Completion> {
+        let s = word.text.as_str();
+        if s == "~" {
+            return vec![Completion::word(Kind::Path, word, "~/")];
+        }
+
+        // Everything up to the last `/` is the directory to look in,
+        // the rest a prefix of the name
+        let (dir, prefix) = match s.rfind('/') {
+            Some(i) => s.split_at(i + 1),
+            None => ("", s),
+        };
+
+        let Some(path) = self.resolve(dir) else {
+            return vec![];
+        };
+        let path = if path.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            &path
+        };
+
+        let Ok(entries) = fs::read_dir(path) else {
+            return vec![];
+        };
+
+        let mut names: Vec<String> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+
+                // Hidden files only when asked for
+                if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.'))
+                {
+                    return None;
+                }
+
+                // Follows symlinks, so a link to a directory is one
+                let is_dir = entry.path().is_dir();
+                Some(format!("{name}{}", if is_dir { "/" } else { "" }))
+            })
+            .collect();
+
+        names.sort();
+        names
+            .into_iter()
+            .map(|text| Completion {
+                text,
+                span: dir.len()..s.len(),
+                kind: Kind::Path,
+                detail: None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nash_parser::partial::context_at;
+    use tempfile::{TempDir, tempdir};
+
+    /// The words `completer` would complete `s` to
+    fn complete(completer: &PathCompleter, s: &str) -> Vec<String> {
+        let word = context_at(s, s.len()).word;
+        completer
+            .complete(&word)
+            .iter()
+            .map(|c| c.apply(&word.text))
+            .collect()
+    }
+
+    /// A fresh directory holding `files`, where names ending in `/`
+    /// are made directories
+    fn tree(files: &[&str]) -> TempDir {
+        let root = tempdir().unwrap();
+
+        for file in files {
+            match file.strip_suffix('/') {
+                Some(dir) => fs::create_dir_all(root.path().join(dir)).unwrap(),
+                None => fs::write(root.path().join(file), "").unwrap(),
+            }
+        }
+
+        root
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        let root = tree(&["src/", "src/main.rs", "Cargo.toml", "Cargo.lock"]);
+        let completer = PathCompleter::new().relative_to(root.path());
+
+        assert_eq!(
+            complete(&completer, "Car"),
+            vec!["Cargo.lock", "Cargo.toml"]
+        );
+        assert_eq!(complete(&completer, "s"), vec!["src/"]);
+        assert_eq!(complete(&completer, "src/"), vec!["src/main.rs"]);
+        assert!(complete(&completer, "nope").is_empty());
+
+        // Only the name is offered, for the menu to show
+        let word = context_at("src/ma", 6).word;
+        let completions = completer.complete(&word);
+        assert_eq!(completions[0].text, "main.rs");
+        assert_eq!(completions[0].span, 4..6);
+    }
+
+    #[test]
+    fn test_absolute_paths() {
+        let root = tree(&["notes.txt"]);
+        let completer = PathCompleter::new();
+        let dir = format!("{}/no", root.path().display());
+
+        assert_eq!(
+            complete(&completer, &dir),
+            vec![format!("{}/notes.txt", root.path().display())]
+        );
+    }
+
+    #[test]
+    fn test_home_paths() {
+        let root = tree(&["Documents/", "Downloads/"]);
+        let completer = PathCompleter::new().with_home(root.path());
+
+        assert_eq!(complete(&completer, "~"), vec!["~/"]);
+        assert_eq!(
+            complete(&completer, "~/Do"),
+            vec!["~/Documents/", "~/Downloads/"]
+        );
+    }
+
+    #[test]
+    fn test_hidden_files_need_a_dot() {
+        let root = tree(&[".env", "env.sh"]);
+        let completer = PathCompleter::new().relative_to(root.path());
+
+        assert_eq!(complete(&completer, ""), vec!["env.sh"]);
+        assert_eq!(complete(&completer, "."), vec![".env"]);
+    }
+}
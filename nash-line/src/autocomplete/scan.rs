@@ -0,0 +1,315 @@
+//! Finding the executables on `PATH`, through a cache on disk
+//!
+//! Adding, removing or renaming an entry bumps its directory's
+//! mtime, so the cache keeps each directory's executables with the
+//! mtime they were read at. Only directories whose mtime has moved
+//! since are read again.
+
+use nash_path::{Resolved, SearchPath};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// First line of a cache file, so a different format is never
+/// mistaken for this one
+const HEADER: &str = "nash path cache 1";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ScanCache {
+    dirs: HashMap<PathBuf, CachedDir>,
+}
+
+#[derive(Debug, PartialEq)]
+struct CachedDir {
+    modified: SystemTime,
+    names: Vec<String>,
+}
+
+impl ScanCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the cache written to `path`, or starts empty when there
+    /// isn't one that can be read.
+    ///
+    /// The format is a header line, then a `secs nanos dir` line for
+    /// each directory followed by its executables, each on a line of
+    /// its own behind a tab.
+    pub fn load(path: &Path) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::new();
+        };
+
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Self::new();
+        }
+
+        let mut dirs: Vec<(PathBuf, CachedDir)> = vec![];
+
+        for line in lines {
+            if let Some(name) = line.strip_prefix('\t') {
+                match dirs.last_mut() {
+                    Some((_, dir)) => dir.names.push(name.to_string()),
+                    None => return Self::new(),
+                }
+                continue;
+            }
+
+            let Some((modified, dir)) = parse_dir_line(line) else {
+                return Self::new();
+            };
+            dirs.push((
+                dir,
+                CachedDir {
+                    modified,
+                    names: vec![],
+                },
+            ));
+        }
+
+        Self {
+            dirs: dirs.into_iter().collect(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut contents = format!("{HEADER}\n");
+
+        for (dir, cached) in &self.dirs {
+            // Paths the format can't hold are read every time instead
+            let Some(dir) = dir.to_str().filter(|d| !d.contains('\n')) else {
+                continue;
+            };
+            let since = cached
+                .modified
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+
+            contents.push_str(&format!(
+                "{} {} {dir}\n",
+                since.as_secs(),
+                since.subsec_nanos()
+            ));
+            for name in &cached.names {
+                contents.push_str(&format!("\t{name}\n"));
+            }
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Written aside and moved into place, so another shell never
+        // reads half a cache
+        let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&partial, contents)?;
+        fs::rename(&partial, path)
+    }
+
+    /// Brings the cache up to date with `dirs`, reading only those
+    /// that changed and forgetting any no longer asked about.
+    /// Returns whether the cache changed.
+    pub fn refresh(&mut self, dirs: &[PathBuf]) -> bool {
+        let before = self.dirs.len();
+        self.dirs.retain(|dir, _| dirs.contains(dir));
+        let mut changed = self.dirs.len() != before;
+
+        for dir in dirs {
+            let Ok(modified) = fs::metadata(dir).and_then(|m| m.modified()) else {
+                changed |= self.dirs.remove(dir).is_some();
+                continue;
+            };
+
+            if self.dirs.get(dir).is_some_and(|d| d.modified == modified) {
+                continue;
+            }
+
+            // Names the cache format can't hold are left out
+            let mut names = nash_path::executables_in(dir);
+            names.retain(|name| !name.contains('\n'));
+            self.dirs.insert(dir.clone(), CachedDir { modified, names });
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// The executables in `dirs` as of the last refresh, each
+    /// resolved as if `dirs` were the search path
+    pub fn executables(&self, dirs: &[PathBuf]) -> Vec<Resolved> {
+        nash_path::resolve(dirs.iter().filter_map(|dir| {
+            let cached = self.dirs.get(dir)?;
+            Some((dir.as_path(), cached.names.as_slice()))
+        }))
+    }
+}
+
+fn parse_dir_line(line: &str) -> Option<(SystemTime, PathBuf)> {
+    let mut parts = line.splitn(3, ' ');
+    let secs = parts.next()?.parse().ok()?;
+    let nanos = parts.next()?.parse().ok()?;
+    let dir = parts.next()?;
+
+    Some((UNIX_EPOCH + Duration::new(secs, nanos), PathBuf::from(dir)))
+}
+
+/// Where the cache lives - under `$XDG_CACHE_HOME`, or `~/.cache`
+pub fn default_location() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+
+    Some(base.join("nash").join("path-cache"))
+}
+
+/// The executables on `PATH`, read through the cache at `cache`,
+/// which is rewritten when anything changed
+pub fn executables_in_path(cache: Option<&Path>) -> Vec<Resolved> {
+    let search = SearchPath::from_env();
+    let dirs = search.dirs();
+    let mut scan = cache.map(ScanCache::load).unwrap_or_default();
+
+    if scan.refresh(dirs)
+        && let Some(cache) = cache
+    {
+        // Failing to cache only costs the next shell a rescan
+        let _ = scan.save(cache);
+    }
+
+    scan.executables(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::File, os::unix::fs::PermissionsExt};
+    use tempfile::{TempDir, tempdir};
+
+    /// A fresh directory `name` inside `root`
+    fn subdir(root: &TempDir, name: &str) -> PathBuf {
+        let dir = root.path().join(name);
+        fs::create_dir(&dir).unwrap();
+        dir
+    }
+
+    fn executable(dir: &Path, name: &str) {
+        let path = dir.join(name);
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn names(cache: &ScanCache, dirs: &[PathBuf]) -> Vec<String> {
+        cache
+            .executables(dirs)
+            .into_iter()
+            .map(|resolved| resolved.name)
+            .collect()
+    }
+
+    fn set_modified(dir: &Path, time: SystemTime) {
+        File::open(dir).unwrap().set_modified(time).unwrap();
+    }
+
+    #[test]
+    fn test_finds_only_executables() {
+        let root = tempdir().unwrap();
+        let dir = subdir(&root, "only");
+        executable(&dir, "run");
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        fs::create_dir(dir.join("sub")).unwrap();
+
+        let mut cache = ScanCache::new();
+        assert!(cache.refresh(std::slice::from_ref(&dir)));
+        assert_eq!(names(&cache, &[dir]), vec!["run"]);
+    }
+
+    #[test]
+    fn test_unchanged_directories_are_not_reread() {
+        let root = tempdir().unwrap();
+        let dir = subdir(&root, "unchanged");
+        let dirs = vec![dir.clone()];
+        executable(&dir, "old");
+        let modified = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        set_modified(&dir, modified);
+
+        let mut cache = ScanCache::new();
+        cache.refresh(&dirs);
+
+        // Same mtime, so the new file goes unseen
+        executable(&dir, "new");
+        set_modified(&dir, modified);
+        assert!(!cache.refresh(&dirs));
+        assert_eq!(names(&cache, &dirs), vec!["old"]);
+
+        set_modified(&dir, modified + Duration::from_secs(1));
+        assert!(cache.refresh(&dirs));
+        assert_eq!(names(&cache, &dirs), vec!["new", "old"]);
+    }
+
+    #[test]
+    fn test_dropped_directories_are_forgotten() {
+        let root = tempdir().unwrap();
+        let (a, b) = (subdir(&root, "a"), subdir(&root, "b"));
+        executable(&a, "in-a");
+        executable(&b, "in-b");
+
+        let mut cache = ScanCache::new();
+        cache.refresh(&[a.clone(), b.clone()]);
+        assert!(cache.refresh(std::slice::from_ref(&b)));
+        assert_eq!(names(&cache, &[a, b]), vec!["in-b"]);
+    }
+
+    #[test]
+    fn test_cached_executables_keep_precedence() {
+        let root = tempdir().unwrap();
+        let (local, system) = (subdir(&root, "local"), subdir(&root, "system"));
+        executable(&local, "python");
+        executable(&system, "python");
+
+        let dirs = vec![local.clone(), system.clone()];
+        let mut cache = ScanCache::new();
+        cache.refresh(&dirs);
+
+        let [python] = cache.executables(&dirs).try_into().unwrap();
+        assert_eq!(python.path, local.join("python"));
+        assert_eq!(python.shadowed, vec![system.join("python")]);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let root = tempdir().unwrap();
+        let dir = subdir(&root, "round-trip");
+        executable(&dir, "tool");
+        executable(&dir, "with space");
+
+        let mut cache = ScanCache::new();
+        cache.refresh(std::slice::from_ref(&dir));
+
+        let file = subdir(&root, "cache").join("nested").join("path-cache");
+        cache.save(&file).unwrap();
+        let loaded = ScanCache::load(&file);
+        assert_eq!(loaded, cache);
+
+        // Nothing changed since it was saved
+        let mut loaded = loaded;
+        assert!(!loaded.refresh(&[dir]));
+    }
+
+    #[test]
+    fn test_unreadable_cache_starts_empty() {
+        let root = tempdir().unwrap();
+        let file = subdir(&root, "garbage").join("path-cache");
+        fs::write(&file, "not a cache\n").unwrap();
+        assert_eq!(ScanCache::load(&file), ScanCache::new());
+        assert_eq!(
+            ScanCache::load(&file.with_extension("missing")),
+            ScanCache::new()
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! What a position in the parse tree wants completed
+//!
+//! Special forms give their operands their own meaning - the last
+//! operand of `out` is a file, the operands of `pipe` are lists - so
+//! a word is completed by what its slot expects rather than by
+//! whether it comes first in a list.
+
+use nash_parser::{
+    forms::Form,
+    partial::{CursorContext, Position},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slot {
+    /// The program a list runs
+    Command,
+    /// An argument passed to a program
+    Argument,
+    /// The file a redirect reads or writes
+    File,
+}
+
+impl Slot {
+    /// The slot the cursor is in, or `None` where nothing sensible
+    /// can be typed - a comment, the name `def` binds, or an
+    /// operand that has to be a list
+    pub fn at(context: &CursorContext) -> Option<Slot> {
+        match &context.position {
+            Position::Comment => None,
+            Position::TopLevel | Position::Command => Some(Slot::Command),
+            Position::Argument { operator, index } => {
+                let form = operator.as_deref().and_then(Form::from_operator);
+                match form {
+                    None => Some(Slot::Argument),
+                    Some(Form::Def) => (*index == 2).then_some(Slot::Argument),
+                    // `(out ls file)` runs `ls`, and anything after
+                    // could be the file
+                    Some(form) if form.is_redirect() => Some(match index {
+                        1 => Slot::Command,
+                        _ => Slot::File,
+                    }),
+                    Some(_) => None,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nash_parser::partial::context_at;
+
+    fn slot(input: &str) -> Option<Slot> {
+        Slot::at(&context_at(input, input.len()))
+    }
+
+    #[test]
+    fn test_commands_and_arguments() {
+        assert_eq!(slot("(gi"), Some(Slot::Command));
+        assert_eq!(slot("(git co"), Some(Slot::Argument));
+        assert_eq!(slot("('out' fi"), Some(Slot::Argument));
+        assert_eq!(slot("(ls) ; a comm"), None);
+    }
+
+    #[test]
+    fn test_redirect_slots() {
+        assert_eq!(slot("(out l"), Some(Slot::Command));
+        assert_eq!(slot("(out (ls) fi"), Some(Slot::File));
+        assert_eq!(slot("(>> ls -la fi"), Some(Slot::File));
+        assert_eq!(slot("(< (cat) 'my fi"), Some(Slot::File));
+    }
+
+    #[test]
+    fn test_forms_without_word_operands() {
+        assert_eq!(slot("(def na"), None);
+        assert_eq!(slot("(def name va"), Some(Slot::Argument));
+        assert_eq!(slot("(pipe (ls) gr"), None);
+        assert_eq!(slot("(if (true) ec"), None);
+    }
+}
@@ -1,17 +1,31 @@
-use std::collections::HashMap;
+use std::{cmp::Reverse, collections::BTreeMap, ops::ControlFlow};
+
+use super::fuzzy::{self, FuzzyMatch};
 
 pub struct TrieNode {
-    pub children: HashMap<char, TrieNode>,
-    pub terminals: Vec<String>,
+    pub children: BTreeMap<char, TrieNode>,
+    pub terminals: Vec<Terminal>,
     pub terminals_in_subtrie: usize,
+    /// The heaviest weight of any terminal in this subtrie
+    pub max_weight: u32,
+}
+
+/// A string ending at a node, in its original casing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Terminal {
+    pub name: String,
+    /// How often, or how recently, the completion was used. Heavier
+    /// terminals rank ahead of lighter ones.
+    pub weight: u32,
 }
 
 impl TrieNode {
     fn new() -> Self {
         TrieNode {
-            children: HashMap::new(),
+            children: BTreeMap::new(),
             terminals: Vec::new(),
             terminals_in_subtrie: 0,
+            max_weight: 0,
         }
     }
 
@@ -24,6 +38,12 @@ pub struct Trie {
     pub root_node: TrieNode,
 }
 
+impl Default for Trie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Trie {
     pub fn new() -> Self {
         Trie {
@@ -31,7 +51,12 @@ impl Trie {
         }
     }
 
-    pub fn insert(&mut self, s: &str) {
+    /// Adds `s`, returning false when it was already there
+    pub fn insert(&mut self, s: &str) -> bool {
+        if self.contains(s) {
+            return false;
+        }
+
         let mut current_node = &mut self.root_node;
 
         for c in s.chars() {
@@ -46,24 +71,240 @@ impl Trie {
         }
 
         current_node.terminals_in_subtrie += 1;
-        current_node.terminals.push(s.to_owned());
+        current_node.terminals.push(Terminal {
+            name: s.to_owned(),
+            weight: 0,
+        });
+
+        true
+    }
+
+    /// Whether `s` was inserted, matched with its casing
+    pub fn contains(&self, s: &str) -> bool {
+        self.find_node(s)
+            .is_some_and(|node| node.terminals.iter().any(|t| t.name == s))
     }
 
+    /// Removes `s`, matched with its casing, along with any nodes
+    /// left leading nowhere. Returns false when it wasn't there.
+    pub fn remove(&mut self, s: &str) -> bool {
+        if !self.contains(s) {
+            return false;
+        }
+
+        let path: Vec<char> = s.chars().map(norm).collect();
+        Self::remove_below(&mut self.root_node, &path, s);
+
+        true
+    }
+
+    /// Removes `s` from the subtrie under `node`, `path` leading to
+    /// its terminal, and recounts what the path passes through
+    fn remove_below(node: &mut TrieNode, path: &[char], s: &str) {
+        node.terminals_in_subtrie -= 1;
+
+        match path.split_first() {
+            None => node.terminals.retain(|t| t.name != s),
+            Some((c, rest)) => {
+                let child = node.children.get_mut(c).expect("s is in the trie");
+                Self::remove_below(child, rest, s);
+
+                if child.terminals_in_subtrie == 0 {
+                    node.children.remove(c);
+                }
+            }
+        }
+
+        node.max_weight = node
+            .terminals
+            .iter()
+            .map(|t| t.weight)
+            .chain(node.children.values().map(|c| c.max_weight))
+            .max()
+            .unwrap_or(0);
+    }
+
+    /// Adds one to the weight of `s`, matched with its casing, so it
+    /// ranks higher from then on. Returns false when `s` isn't in
+    /// the trie.
+    pub fn bump(&mut self, s: &str) -> bool {
+        let Some(weight) = self
+            .find_node(s)
+            .and_then(|node| node.terminals.iter().find(|t| t.name == s))
+            .map(|t| t.weight + 1)
+        else {
+            return false;
+        };
+
+        let mut current_node = &mut self.root_node;
+        for c in s.chars() {
+            current_node.max_weight = current_node.max_weight.max(weight);
+            current_node = current_node
+                .children
+                .get_mut(&norm(c))
+                .expect("path was just found");
+        }
+
+        current_node.max_weight = current_node.max_weight.max(weight);
+        if let Some(terminal) = current_node.terminals.iter_mut().find(|t| t.name == s) {
+            terminal.weight = weight;
+        }
+
+        true
+    }
+
+    /// Every completion of `s`, best first - see [`rank`]
     pub fn get_completions(&self, s: &str) -> Option<Vec<String>> {
-        let prefix_node = self.find_node(s);
+        let node = self.find_node(s)?;
+        let mut completions = Vec::with_capacity(node.terminals_in_subtrie);
+
+        let _ = Self::dfs(node, &mut |terminal| {
+            completions.push(terminal);
+            ControlFlow::Continue(())
+        });
+
+        Some(rank(s, completions, usize::MAX))
+    }
+
+    /// The best `n` completions of `s`, ranked as
+    /// [`Trie::get_completions`] would.
+    ///
+    /// The subtrie is walked a level at a time, stopping after the
+    /// level that reaches `n` - anything deeper is longer, so ranks
+    /// lower unless it has been weighted. Weighted terminals are
+    /// still picked up from below, where `max_weight` says there
+    /// are any.
+    pub fn get_completions_limited(&self, s: &str, n: usize) -> Option<Vec<String>> {
+        let node = self.find_node(s)?;
+        let mut completions = Vec::with_capacity(n.min(node.terminals_in_subtrie));
+        if n == 0 {
+            return Some(vec![]);
+        }
+
+        let mut level = vec![node];
+        while !level.is_empty() && completions.len() < n {
+            let mut next = vec![];
+            for node in level {
+                completions.extend(&node.terminals);
+                next.extend(node.children.values());
+            }
+            level = next;
+        }
+
+        for node in level {
+            Self::weighted(node, &mut completions);
+        }
 
-        if let Some(node) = prefix_node {
-            let mut completions = Vec::new();
-            let mut prefix = s.to_owned();
+        Some(rank(s, completions, n))
+    }
+
+    /// Everything `pattern` matches as a subsequence, best scoring
+    /// first - see [`fuzzy`]. Subtries are only scored once the
+    /// path into them has taken up the whole pattern.
+    pub fn fuzzy_matches(&self, pattern: &str) -> Vec<FuzzyMatch> {
+        let pattern_chars: Vec<char> = pattern.chars().map(norm).collect();
+        let mut found = vec![];
+        Self::subsequence_below(&self.root_node, &pattern_chars, &mut found);
+
+        let mut matches: Vec<(FuzzyMatch, u32)> = found
+            .into_iter()
+            .filter_map(|t| Some((fuzzy::score(pattern, &t.name)?, t.weight)))
+            .collect();
+
+        matches.sort_by_cached_key(|(m, weight)| {
+            (
+                Reverse(m.score),
+                Reverse(*weight),
+                m.name.chars().count(),
+                m.name.to_lowercase(),
+                m.name.clone(),
+            )
+        });
+
+        matches.into_iter().map(|(m, _)| m).collect()
+    }
 
-            Self::dfs(node, &mut prefix, &mut completions);
+    /// Collects the terminals under `node` that `pattern` is a
+    /// subsequence of, with what's left of it to match
+    fn subsequence_below<'a>(node: &'a TrieNode, pattern: &[char], found: &mut Vec<&'a Terminal>) {
+        let Some((first, rest)) = pattern.split_first() else {
+            let _ = Self::dfs(node, &mut |terminal| {
+                found.push(terminal);
+                ControlFlow::Continue(())
+            });
+            return;
+        };
 
-            Some(completions)
-        } else {
-            None
+        for (c, child_node) in &node.children {
+            let remaining = if c == first { rest } else { pattern };
+            Self::subsequence_below(child_node, remaining, found);
         }
     }
 
+    /// How many completions there are for `s`, read from the cached
+    /// counts rather than collecting them
+    pub fn count_completions(&self, s: &str) -> usize {
+        self.find_node(s)
+            .map_or(0, |node| node.terminals_in_subtrie)
+    }
+
+    /// The longest prefix shared by every completion of `s`, or
+    /// `None` when there are none.
+    ///
+    /// Where the completions agree on the case of a char, that case
+    /// is kept. Where they don't, the char is taken as typed in `s`,
+    /// or lowercased past the end of it.
+    pub fn longest_common_prefix(&self, s: &str) -> Option<String> {
+        let mut node = self.find_node(s)?;
+        if node.terminals_in_subtrie == 0 {
+            return None;
+        }
+
+        // Follow the only way down until the completions branch
+        // off, or one of them ends
+        let mut extension = String::new();
+        while !node.completes_token() && node.children.len() == 1 {
+            let (c, child) = node.children.iter().next()?;
+            extension.push(*c);
+            node = child;
+        }
+
+        if extension.is_empty() {
+            return Some(s.to_owned());
+        }
+
+        let typed: Vec<char> = s.chars().collect();
+        let len = typed.len() + extension.chars().count();
+
+        // The case every completion agrees on at each position,
+        // `None` once they differ
+        let mut agreed: Option<Vec<Option<char>>> = None;
+        let _ = Self::dfs(node, &mut |terminal| {
+            let chars = terminal.name.chars().take(len);
+            match &mut agreed {
+                None => agreed = Some(chars.map(Some).collect()),
+                Some(agreed) => {
+                    for (slot, c) in agreed.iter_mut().zip(chars) {
+                        if *slot != Some(c) {
+                            *slot = None;
+                        }
+                    }
+                }
+            }
+            ControlFlow::Continue(())
+        });
+
+        let fallback = typed.into_iter().chain(extension.chars());
+        let prefix = agreed
+            .unwrap_or_default()
+            .into_iter()
+            .zip(fallback)
+            .map(|(agreed, fallback)| agreed.unwrap_or(fallback))
+            .collect();
+
+        Some(prefix)
+    }
+
     fn find_node(&self, s: &str) -> Option<&TrieNode> {
         let mut current_node = &self.root_node;
 
@@ -71,7 +312,7 @@ impl Trie {
             let c_norm = norm(c);
 
             if let Some(child_node) = current_node.children.get(&c_norm) {
-                current_node = &child_node;
+                current_node = child_node;
             } else {
                 return None;
             }
@@ -80,17 +321,74 @@ impl Trie {
         Some(current_node)
     }
 
-    fn dfs(node: &TrieNode, prefix: &mut String, completions: &mut Vec<String>) {
-        if node.completes_token() {
-            completions.extend(node.terminals.iter().cloned());
+    /// Visits every terminal under `node` until `visit` breaks
+    fn dfs<'a>(
+        node: &'a TrieNode,
+        visit: &mut impl FnMut(&'a Terminal) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        for terminal in &node.terminals {
+            visit(terminal)?;
         }
 
-        for (c, child_node) in &node.children {
-            prefix.push(*c);
-            Self::dfs(child_node, prefix, completions);
-            prefix.pop();
+        for child_node in node.children.values() {
+            Self::dfs(child_node, visit)?;
         }
+
+        ControlFlow::Continue(())
     }
+
+    /// Collects the weighted terminals under `node`, skipping
+    /// subtries that have none
+    fn weighted<'a>(node: &'a TrieNode, found: &mut Vec<&'a Terminal>) {
+        if node.max_weight == 0 {
+            return;
+        }
+
+        found.extend(node.terminals.iter().filter(|t| t.weight > 0));
+        for child_node in node.children.values() {
+            Self::weighted(child_node, found);
+        }
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for Trie {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<S: AsRef<str>> Extend<S> for Trie {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        for s in iter {
+            self.insert(s.as_ref());
+        }
+    }
+}
+
+/// Orders completions of `s` best first, keeping at most `n`: an
+/// exact match, then heavier weights, then shorter names, then
+/// alphabetically
+fn rank(s: &str, mut completions: Vec<&Terminal>, n: usize) -> Vec<String> {
+    let s: String = s.chars().map(norm).collect();
+
+    completions.sort_by_cached_key(|t| {
+        let normalized: String = t.name.chars().map(norm).collect();
+        (
+            normalized != s,
+            Reverse(t.weight),
+            t.name.chars().count(),
+            normalized,
+            t.name.clone(),
+        )
+    });
+
+    completions
+        .into_iter()
+        .take(n)
+        .map(|t| t.name.clone())
+        .collect()
 }
 
 fn norm(c: char) -> char {
@@ -109,26 +407,19 @@ mod tests {
         trie
     }
 
-    fn sorted(v: Option<Vec<String>>) -> Option<Vec<String>> {
-        v.map(|mut s| {
-            s.sort();
-            s
-        })
-    }
-
     // ── insert / basic retrieval ─────────────────────────────────────────────
 
     #[test]
     fn returns_none_for_unknown_prefix() {
         let trie = trie_with(&["hello", "world"]);
-        assert_eq!(trie.get_completions(&mut "xyz".to_owned()), None);
+        assert_eq!(trie.get_completions("xyz"), None);
     }
 
     #[test]
     fn returns_exact_match() {
         let trie = trie_with(&["hello"]);
         assert_eq!(
-            sorted(trie.get_completions(&mut "hello".to_owned())),
+            trie.get_completions("hello"),
             Some(vec!["hello".to_owned()])
         );
     }
@@ -137,20 +428,21 @@ mod tests {
     fn returns_all_completions_for_shared_prefix() {
         let trie = trie_with(&["cat", "car", "card", "care", "dog"]);
         assert_eq!(
-            sorted(trie.get_completions(&mut "car".to_owned())),
+            trie.get_completions("car"),
             Some(vec!["car".to_owned(), "card".to_owned(), "care".to_owned()])
         );
     }
 
     #[test]
     fn empty_prefix_returns_all_words() {
-        let words = ["alpha", "beta", "gamma"];
-        let trie = trie_with(&words);
-        let mut expected: Vec<String> = words.iter().map(|s| s.to_string()).collect();
-        expected.sort();
+        let trie = trie_with(&["alpha", "beta", "gamma"]);
         assert_eq!(
-            sorted(trie.get_completions(&mut "".to_owned())),
-            Some(expected)
+            trie.get_completions(""),
+            Some(vec![
+                "beta".to_owned(),
+                "alpha".to_owned(),
+                "gamma".to_owned()
+            ])
         );
     }
 
@@ -160,7 +452,7 @@ mod tests {
     fn uppercase_prefix_matches_lowercase_insertions() {
         let trie = trie_with(&["git", "grep"]);
         assert_eq!(
-            sorted(trie.get_completions(&mut "G".to_owned())),
+            trie.get_completions("G"),
             Some(vec!["git".to_owned(), "grep".to_owned()])
         );
     }
@@ -169,7 +461,7 @@ mod tests {
     fn lowercase_prefix_matches_uppercase_insertions() {
         let trie = trie_with(&["GIT", "GREP"]);
         assert_eq!(
-            sorted(trie.get_completions(&mut "g".to_owned())),
+            trie.get_completions("g"),
             Some(vec!["GIT".to_owned(), "GREP".to_owned()])
         );
     }
@@ -177,10 +469,10 @@ mod tests {
     #[test]
     fn completions_preserve_original_casing() {
         let trie = trie_with(&["CamelCase", "camelback"]);
-        let result = sorted(trie.get_completions(&mut "camel".to_owned()));
+        let result = trie.get_completions("camel");
         assert_eq!(
             result,
-            Some(vec!["CamelCase".to_owned(), "camelback".to_owned()])
+            Some(vec!["camelback".to_owned(), "CamelCase".to_owned()])
         );
     }
 
@@ -196,10 +488,221 @@ mod tests {
     // ── duplicates ───────────────────────────────────────────────────────────
 
     #[test]
-    fn inserting_same_word_twice_returns_it_twice() {
-        let trie = trie_with(&["echo", "echo"]);
-        let result = trie.get_completions(&mut "echo".to_owned()).unwrap();
-        assert_eq!(result.len(), 2);
-        assert!(result.iter().all(|s| s == "echo"));
+    fn inserting_same_word_twice_stores_it_once() {
+        let mut trie = trie_with(&["echo"]);
+        assert!(!trie.insert("echo"));
+        assert_eq!(trie.get_completions("echo"), Some(vec!["echo".to_owned()]));
+        assert_eq!(trie.count_completions(""), 1);
+    }
+
+    #[test]
+    fn words_differing_in_case_are_both_kept() {
+        let trie = trie_with(&["echo", "Echo"]);
+        assert_eq!(trie.count_completions("echo"), 2);
+    }
+
+    // ── contains / remove ────────────────────────────────────────────────────
+
+    #[test]
+    fn contains_matches_casing() {
+        let trie = trie_with(&["Makefile", "make"]);
+        assert!(trie.contains("make"));
+        assert!(trie.contains("Makefile"));
+        assert!(!trie.contains("makefile"));
+        assert!(!trie.contains("mak"));
+    }
+
+    #[test]
+    fn remove_takes_word_out() {
+        let mut trie = trie_with(&["cat", "car", "card"]);
+        assert!(trie.remove("car"));
+        assert!(!trie.contains("car"));
+        assert_eq!(
+            trie.get_completions("ca"),
+            Some(vec!["cat".to_owned(), "card".to_owned()])
+        );
+        assert_eq!(trie.count_completions("ca"), 2);
+        assert!(!trie.remove("car"));
+        assert!(!trie.remove("CAT"));
+    }
+
+    #[test]
+    fn remove_prunes_empty_nodes() {
+        let mut trie = trie_with(&["git", "gitk"]);
+        trie.remove("gitk");
+        assert_eq!(trie.get_completions("gitk"), None);
+
+        trie.remove("git");
+        assert!(trie.root_node.children.is_empty());
+        assert_eq!(trie.root_node.terminals_in_subtrie, 0);
+        assert_eq!(trie.get_completions(""), Some(vec![]));
+    }
+
+    #[test]
+    fn remove_recomputes_weights() {
+        let mut trie = trie_with(&["ls", "lsof"]);
+        trie.bump("lsof");
+        trie.remove("lsof");
+        assert_eq!(trie.root_node.max_weight, 0);
+    }
+
+    #[test]
+    fn bulk_construction_dedups() {
+        let mut trie: Trie = ["tar", "tee", "tar"].into_iter().collect();
+        assert_eq!(trie.count_completions("t"), 2);
+
+        trie.extend(vec!["top".to_string(), "tee".to_string()]);
+        assert_eq!(
+            trie.get_completions("t"),
+            Some(vec!["tar".to_owned(), "tee".to_owned(), "top".to_owned()])
+        );
+    }
+
+    // ── longest common prefix ────────────────────────────────────────────────
+
+    #[test]
+    fn lcp_extends_to_where_completions_branch() {
+        let trie = trie_with(&["groupadd", "groupdel", "groups"]);
+        assert_eq!(trie.longest_common_prefix("g"), Some("group".to_owned()));
+    }
+
+    #[test]
+    fn lcp_stops_at_a_complete_word() {
+        let trie = trie_with(&["git", "gitk"]);
+        assert_eq!(trie.longest_common_prefix("g"), Some("git".to_owned()));
+    }
+
+    #[test]
+    fn lcp_without_extension_is_input() {
+        let trie = trie_with(&["cat", "cp"]);
+        assert_eq!(trie.longest_common_prefix("C"), Some("C".to_owned()));
+        assert_eq!(trie.longest_common_prefix("x"), None);
+    }
+
+    #[test]
+    fn lcp_keeps_agreed_casing() {
+        let trie = trie_with(&["NetworkManager", "NetworkMonitor"]);
+        assert_eq!(
+            trie.longest_common_prefix("net"),
+            Some("NetworkM".to_owned())
+        );
+    }
+
+    #[test]
+    fn lcp_falls_back_when_casing_differs() {
+        let trie = trie_with(&["Makefile.sh", "makedepend"]);
+        assert_eq!(trie.longest_common_prefix("Ma"), Some("Make".to_owned()));
+        assert_eq!(trie.longest_common_prefix("m"), Some("make".to_owned()));
+    }
+
+    // ── counts and limits ────────────────────────────────────────────────────
+
+    #[test]
+    fn count_completions_uses_cached_counts() {
+        let trie = trie_with(&["cat", "car", "card", "dog"]);
+        assert_eq!(trie.count_completions("ca"), 3);
+        assert_eq!(trie.count_completions("CAR"), 2);
+        assert_eq!(trie.count_completions(""), 4);
+        assert_eq!(trie.count_completions("x"), 0);
+    }
+
+    #[test]
+    fn limited_completions_stop_early() {
+        let trie = trie_with(&["cat", "car", "card", "care", "dog"]);
+        let limited = trie.get_completions_limited("ca", 2).unwrap();
+        assert_eq!(limited.len(), 2);
+        assert!(limited.iter().all(|s| s.starts_with("ca")));
+
+        assert_eq!(trie.get_completions_limited("ca", 10).unwrap().len(), 4);
+        assert_eq!(trie.get_completions_limited("ca", 0), Some(vec![]));
+        assert_eq!(trie.get_completions_limited("x", 3), None);
+    }
+
+    // ── fuzzy ────────────────────────────────────────────────────────────────
+
+    fn fuzzy_names(trie: &Trie, pattern: &str) -> Vec<String> {
+        trie.fuzzy_matches(pattern)
+            .into_iter()
+            .map(|m| m.name)
+            .collect()
+    }
+
+    #[test]
+    fn fuzzy_finds_subsequences() {
+        let trie = trie_with(&["docker-compose", "docker", "dmesg", "cmp"]);
+        assert_eq!(fuzzy_names(&trie, "dkcmp"), vec!["docker-compose"]);
+        assert_eq!(fuzzy_names(&trie, "xyz"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn fuzzy_ranks_by_score() {
+        let trie = trie_with(&["git-receive-pack", "gitk", "grep-changelog"]);
+        let matches = trie.fuzzy_matches("gk");
+        assert_eq!(matches[0].name, "gitk");
+        assert_eq!(matches[0].positions, vec![0, 3]);
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[test]
+    fn fuzzy_keeps_casing_and_positions() {
+        let trie = trie_with(&["NetworkManager"]);
+        let matches = trie.fuzzy_matches("nwm");
+        assert_eq!(matches[0].name, "NetworkManager");
+        assert_eq!(matches[0].positions, vec![0, 3, 7]);
+    }
+
+    // ── ranking ──────────────────────────────────────────────────────────────
+
+    fn completions(trie: &Trie, s: &str) -> Vec<String> {
+        trie.get_completions(s).unwrap_or_default()
+    }
+
+    #[test]
+    fn exact_match_ranks_first_then_shorter_then_alphabetical() {
+        let trie = trie_with(&["gzip", "git-lfs", "gitk", "gawk", "Git"]);
+        assert_eq!(completions(&trie, "git"), vec!["Git", "gitk", "git-lfs"]);
+        assert_eq!(
+            completions(&trie, "g"),
+            vec!["Git", "gawk", "gitk", "gzip", "git-lfs"]
+        );
+    }
+
+    #[test]
+    fn order_does_not_depend_on_insertion() {
+        let forward = trie_with(&["cc", "cat", "cut", "cmp"]);
+        let backward = trie_with(&["cmp", "cut", "cat", "cc"]);
+        assert_eq!(completions(&forward, "c"), completions(&backward, "c"));
+        assert_eq!(completions(&forward, "c"), vec!["cc", "cat", "cmp", "cut"]);
+    }
+
+    #[test]
+    fn bumped_completions_rank_higher() {
+        let mut trie = trie_with(&["cat", "cargo", "cal"]);
+        assert!(trie.bump("cargo"));
+        assert_eq!(completions(&trie, "ca"), vec!["cargo", "cal", "cat"]);
+
+        // an exact match still comes first
+        assert_eq!(completions(&trie, "cat"), vec!["cat"]);
+        assert!(trie.bump("cat"));
+        assert!(trie.bump("cat"));
+        assert_eq!(completions(&trie, "ca"), vec!["cat", "cargo", "cal"]);
+
+        assert!(!trie.bump("CAT"));
+        assert!(!trie.bump("dog"));
+    }
+
+    #[test]
+    fn limited_completions_find_deep_weighted_terminals() {
+        let mut trie = trie_with(&["ls", "lsof", "lsblk", "lspci-extended"]);
+        assert_eq!(
+            trie.get_completions_limited("l", 2).unwrap(),
+            vec!["ls", "lsof"]
+        );
+
+        trie.bump("lspci-extended");
+        assert_eq!(
+            trie.get_completions_limited("l", 2).unwrap(),
+            vec!["lspci-extended", "ls"]
+        );
     }
 }
@@ -1,4 +1,7 @@
-use std::fmt::{self};
+use std::{
+    fmt::{self},
+    ops::Range,
+};
 
 #[derive(Debug)]
 pub struct Buffer {
@@ -26,6 +29,7 @@ impl Buffer {
         self.right.pop();
     }
 
+    /// Replaces everything with `s`, leaving the cursor at the end
     pub fn replace(&mut self, s: &str) {
         self.left.clear();
         self.right.clear();
@@ -34,6 +38,18 @@ impl Buffer {
         }
     }
 
+    /// Everything left of the cursor
+    pub fn before_cursor(&self) -> String {
+        self.left.iter().collect()
+    }
+
+    /// Replaces what lies between char offset `start` and the
+    /// cursor with `s`, leaving the cursor after it
+    pub fn replace_before_cursor(&mut self, start: usize, s: &str) {
+        self.left.truncate(start);
+        self.left.extend(s.chars());
+    }
+
     pub fn move_cursor_left(&mut self) {
         if let Some(c) = self.left.pop() {
             self.right.push(c);
@@ -46,6 +62,74 @@ impl Buffer {
         }
     }
 
+    /// Moves to the start of the word left of the cursor
+    pub fn move_word_left(&mut self) {
+        for _ in 0..self.word_left() {
+            self.move_cursor_left();
+        }
+    }
+
+    /// Moves to the end of the word right of the cursor
+    pub fn move_word_right(&mut self) {
+        for _ in 0..self.word_right() {
+            self.move_cursor_right();
+        }
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        self.left.extend(s.chars());
+    }
+
+    /// Removes everything right of the cursor, returning it
+    pub fn kill_to_end(&mut self) -> String {
+        self.right.drain(..).rev().collect()
+    }
+
+    /// Removes everything left of the cursor, returning it
+    pub fn kill_to_start(&mut self) -> String {
+        self.left.drain(..).collect()
+    }
+
+    /// Removes back to the start of the word left of the cursor,
+    /// returning it
+    pub fn kill_word_left(&mut self) -> String {
+        let start = self.left.len() - self.word_left();
+        self.left.split_off(start).into_iter().collect()
+    }
+
+    /// Removes up to the end of the word right of the cursor,
+    /// returning it
+    pub fn kill_word_right(&mut self) -> String {
+        let end = self.right.len() - self.word_right();
+        self.right.split_off(end).into_iter().rev().collect()
+    }
+
+    /// Swaps the chars either side of the cursor and moves past
+    /// both. At the end of the line the last two swap instead.
+    pub fn transpose(&mut self) {
+        if self.right.is_empty() && self.left.len() >= 2 {
+            self.move_cursor_left();
+        }
+
+        if let (Some(&before), Some(&after)) = (self.left.last(), self.right.last()) {
+            self.left.pop();
+            self.right.pop();
+            self.left.push(after);
+            self.left.push(before);
+        }
+    }
+
+    /// How far left the previous word starts - past any separators
+    /// next to the cursor, then the word itself
+    fn word_left(&self) -> usize {
+        word_distance(self.left.iter().rev())
+    }
+
+    /// How far right the next word ends
+    fn word_right(&self) -> usize {
+        word_distance(self.right.iter().rev())
+    }
+
     pub fn move_cursor_home(&mut self) {
         while let Some(c) = self.left.pop() {
             self.right.push(c);
@@ -62,6 +146,37 @@ impl Buffer {
         self.left.len()
     }
 
+    pub fn len(&self) -> usize {
+        self.left.len() + self.right.len()
+    }
+
+    pub fn chars(&self) -> Vec<char> {
+        self.left
+            .iter()
+            .chain(self.right.iter().rev())
+            .copied()
+            .collect()
+    }
+
+    /// Moves the cursor to char offset `col`, or the end if the line
+    /// is shorter
+    pub fn set_cursor(&mut self, col: usize) {
+        while self.left.len() > col {
+            self.move_cursor_left();
+        }
+        while self.left.len() < col && !self.right.is_empty() {
+            self.move_cursor_right();
+        }
+    }
+
+    /// Removes the chars in `range`, returning them, and leaves the
+    /// cursor where they were
+    pub fn remove(&mut self, range: Range<usize>) -> String {
+        self.set_cursor(range.end);
+        let start = range.start.min(self.left.len());
+        self.left.split_off(start).into_iter().collect()
+    }
+
     pub fn as_display(&self) -> BufferDisplay<'_> {
         BufferDisplay(self)
     }
@@ -83,16 +198,23 @@ impl Buffer {
 
         s
     }
+}
 
-    pub fn take_until_highlighted(&self) -> String {
-        let right_until_whitespace = self.right.iter().rev().take_while(|&&c| !c.is_whitespace());
+/// Whether `c` belongs to a word. Whitespace and the chars that
+/// delimit nash tokens - parens and quotes - separate words.
+pub fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | '"' | '\'')
+}
 
-        self.left
-            .iter()
-            .chain(right_until_whitespace)
-            .copied()
-            .collect()
-    }
+/// How many of `chars`, walking away from the cursor, it takes to
+/// get past the next word
+fn word_distance<'a>(chars: impl Iterator<Item = &'a char> + Clone) -> usize {
+    let separators = chars.clone().take_while(|&&c| !is_word_char(c)).count();
+    let word = chars
+        .skip(separators)
+        .take_while(|&&c| is_word_char(c))
+        .count();
+    separators + word
 }
 
 pub struct BufferDisplay<'a>(&'a Buffer);
@@ -254,6 +376,20 @@ mod tests {
         assert_eq!(buffer.cursor_column(), 0);
     }
 
+    #[test]
+    fn test_replace_before_cursor() {
+        let mut buffer = Buffer::new();
+        buffer.replace("(gi x)");
+        buffer.move_cursor_left();
+        buffer.move_cursor_left();
+        buffer.move_cursor_left();
+        assert_eq!(buffer.before_cursor(), "(gi");
+
+        buffer.replace_before_cursor(1, "git");
+        assert_eq!(buffer.as_display().to_string(), "(git x)");
+        assert_eq!(buffer.cursor_column(), 4);
+    }
+
     #[test]
     fn test_take_string_on_empty_buffer() {
         let mut buffer = Buffer::new();
@@ -293,4 +429,106 @@ mod tests {
         assert_eq!(buffer.take_string(), "");
         assert_eq!(buffer.cursor_column(), 0);
     }
+
+    /// A buffer holding `s` with the cursor at the `|` in it
+    fn at(s: &str) -> Buffer {
+        let (left, right) = s.split_once('|').unwrap();
+        let mut buffer = Buffer::new();
+        buffer.replace(&format!("{left}{right}"));
+        for _ in right.chars() {
+            buffer.move_cursor_left();
+        }
+        buffer
+    }
+
+    /// The buffer's text with a `|` at the cursor
+    fn shown(buffer: &Buffer) -> String {
+        let text = buffer.as_display().to_string();
+        let split = text
+            .char_indices()
+            .nth(buffer.cursor_column())
+            .map_or(text.len(), |(i, _)| i);
+        format!("{}|{}", &text[..split], &text[split..])
+    }
+
+    #[test]
+    fn test_word_movement_stops_at_parens() {
+        let mut buffer = at("(git (log -n 3))|");
+        buffer.move_word_left();
+        assert_eq!(shown(&buffer), "(git (log -n |3))");
+        buffer.move_word_left();
+        buffer.move_word_left();
+        assert_eq!(shown(&buffer), "(git (|log -n 3))");
+        buffer.move_word_left();
+        assert_eq!(shown(&buffer), "(|git (log -n 3))");
+
+        buffer.move_word_right();
+        assert_eq!(shown(&buffer), "(git| (log -n 3))");
+        buffer.move_word_right();
+        assert_eq!(shown(&buffer), "(git (log| -n 3))");
+    }
+
+    #[test]
+    fn test_words_stop_at_quotes() {
+        let mut buffer = at("(echo \"hello world\")|");
+        buffer.move_word_left();
+        assert_eq!(shown(&buffer), "(echo \"hello |world\")");
+    }
+
+    #[test]
+    fn test_kill_words() {
+        let mut buffer = at("(git status|)");
+        assert_eq!(buffer.kill_word_left(), "status");
+        assert_eq!(shown(&buffer), "(git |)");
+
+        let mut buffer = at("(|git status)");
+        assert_eq!(buffer.kill_word_right(), "git");
+        assert_eq!(shown(&buffer), "(| status)");
+        assert_eq!(buffer.kill_word_right(), " status");
+        assert_eq!(shown(&buffer), "(|)");
+    }
+
+    #[test]
+    fn test_kill_to_ends() {
+        let mut buffer = at("(ls| -la)");
+        assert_eq!(buffer.kill_to_end(), " -la)");
+        assert_eq!(shown(&buffer), "(ls|");
+
+        let mut buffer = at("(ls| -la)");
+        assert_eq!(buffer.kill_to_start(), "(ls");
+        assert_eq!(shown(&buffer), "| -la)");
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mut buffer = at("(s|l)");
+        buffer.transpose();
+        assert_eq!(shown(&buffer), "(ls|)");
+
+        let mut buffer = at("(ls|");
+        buffer.transpose();
+        assert_eq!(shown(&buffer), "(sl|");
+
+        let mut buffer = at("|ab");
+        buffer.transpose();
+        assert_eq!(shown(&buffer), "|ab");
+
+        let mut buffer = at("a|");
+        buffer.transpose();
+        assert_eq!(shown(&buffer), "a|");
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let mut buffer = at("(git |status)");
+        assert_eq!(buffer.chars().len(), buffer.len());
+
+        assert_eq!(buffer.remove(1..5), "git ");
+        assert_eq!(shown(&buffer), "(|status)");
+
+        buffer.set_cursor(99);
+        assert_eq!(shown(&buffer), "(status)|");
+        assert_eq!(buffer.remove(7..20), ")");
+        assert_eq!(shown(&buffer), "(status|");
+    }
 }
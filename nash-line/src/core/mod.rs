@@ -3,8 +3,26 @@
 //! Manages the input buffer and how incoming
 //! editor events mutate it
 
-use crate::buffer::{Buffer, BufferDisplay};
+use std::{collections::HashMap, ops::Range};
+
+use nash_parser::partial::{self, WordKind};
+
+use crate::{
+    autocomplete::{Completer, Completion, escape, slot::Slot, trie::Trie},
+    buffer::{Buffer, BufferDisplay},
+    history::History,
+    killring::KillRing,
+};
 use EditorEvent::*;
+use vi::Vi;
+
+mod vi;
+
+pub use vi::ViMode;
+
+/// More completion candidates than this and the user is asked
+/// before the menu opens
+const LIST_WITHOUT_ASKING: usize = 36;
 
 pub enum EditorEvent {
     Char(char),
@@ -12,16 +30,147 @@ pub enum EditorEvent {
     Delete,
     Enter,
     Tab,
+    BackTab,
+    Escape,
     Left,
     Right,
+    Up,
+    Down,
     Home,
     End,
     CtrlC,
+    CtrlG,
     CtrlJ,
+    CtrlR,
+    CtrlA,
+    CtrlE,
+    CtrlB,
+    CtrlF,
+    CtrlK,
+    CtrlU,
+    CtrlW,
+    CtrlY,
+    CtrlT,
+    AltB,
+    AltF,
+    AltD,
+    AltY,
 }
 
 pub struct EditorCore {
     buffer: Buffer,
+    /// What completes each slot. Slots without one ring the bell.
+    completers: HashMap<Slot, Box<dyn Completer>>,
+    notice: Option<Notice>,
+    menu: Option<Menu>,
+    /// A menu waiting on the user to confirm they want it
+    pending: Option<Menu>,
+    history: History,
+    /// Set while Up and Down are walking history
+    recall: Option<Recall>,
+    /// Set while Ctrl-R is searching history
+    search: Option<Search>,
+    kills: KillRing,
+    /// What the event before did, which decides whether a kill joins
+    /// the last and whether yank-pop can run
+    last: LastEdit,
+    /// Set when editing the vi way
+    vi: Option<Vi>,
+}
+
+/// Something to show the user once, alongside the line
+#[derive(PartialEq, Debug)]
+pub enum Notice {
+    /// Nothing to complete
+    Bell,
+    /// Asks whether to list this many candidates
+    Confirm(usize),
+}
+
+#[derive(Clone, Copy, Default)]
+enum LastEdit {
+    #[default]
+    Other,
+    Kill,
+    /// Yanked text, which runs from `start` to the cursor
+    Yank {
+        start: usize,
+    },
+}
+
+/// Where Up and Down have got to in history
+struct Recall {
+    index: usize,
+    /// The line as it was before history was walked, which going
+    /// back down past the newest entry restores
+    draft: String,
+}
+
+/// A reverse incremental search through history. The line being
+/// written is left alone until a match is accepted.
+#[derive(Default)]
+struct Search {
+    query: String,
+    /// The history entry found
+    matched: Option<usize>,
+    /// Where the query starts in that entry
+    at: usize,
+    /// Whether the last step found nothing, leaving `matched` as
+    /// it was
+    failed: bool,
+}
+
+impl Search {
+    /// Moves to the newest entry before `before` holding the query
+    fn find(&mut self, history: &History, before: usize) {
+        let found = (0..before).rev().find_map(|i| {
+            let at = history.get(i)?.find(&self.query)?;
+            Some((i, at))
+        });
+
+        match found {
+            Some((i, at)) => {
+                self.matched = Some(i);
+                self.at = at;
+                self.failed = false;
+            }
+            None => self.failed = true,
+        }
+    }
+}
+
+/// Completion candidates the user can tab through
+struct Menu {
+    candidates: Vec<Completion>,
+    selected: Option<usize>,
+    /// Whose candidates they are
+    slot: Slot,
+    /// Where the word being completed starts, and what it was
+    /// before completion touched it
+    start: usize,
+    original: String,
+    /// The word as the lexer reads it, which candidates apply to
+    word: String,
+    /// The quote of the literal being completed in, if it is one
+    quote: Option<char>,
+}
+
+impl Menu {
+    /// The selected candidate as it goes in the buffer
+    fn written(&self) -> String {
+        let completion = &self.candidates[self.selected.unwrap_or_default()];
+        written(&completion.apply(&self.word), self.quote)
+    }
+
+    fn select_next(&mut self) {
+        let len = self.candidates.len();
+        self.selected = Some(self.selected.map_or(0, |i| (i + 1) % len));
+    }
+
+    fn select_previous(&mut self) {
+        let len = self.candidates.len();
+        self.selected = Some(self.selected.map_or(len - 1, |i| (i + len - 1) % len));
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -35,53 +184,454 @@ impl EditorCore {
     pub fn new() -> Self {
         EditorCore {
             buffer: Buffer::new(),
+            completers: HashMap::new(),
+            notice: None,
+            menu: None,
+            pending: None,
+            history: History::new(),
+            recall: None,
+            search: None,
+            kills: KillRing::new(),
+            last: LastEdit::Other,
+            vi: None,
         }
     }
 
+    /// Edits the vi way, starting each line in insert mode
+    pub fn with_vi_mode(mut self) -> Self {
+        self.vi = Some(Vi::new());
+        self
+    }
+
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Completes words in `slot` with `completer`
+    pub fn with_completer(mut self, slot: Slot, completer: impl Completer + 'static) -> Self {
+        self.completers.insert(slot, Box::new(completer));
+        self
+    }
+
     pub fn handle(&mut self, ev: EditorEvent) -> Step {
+        self.notice = None;
+        let last = std::mem::take(&mut self.last);
+
+        // Answering whether to list candidates uses up the event
+        if let Some(menu) = self.pending.take() {
+            if let Char('y' | 'Y' | ' ') = ev {
+                self.menu = Some(menu);
+            }
+            return Step::Continue;
+        }
+
+        if self.search.is_some()
+            && let Some(step) = self.handle_search(&ev)
+        {
+            return step;
+        }
+
+        // Anything the menu doesn't use closes it, keeping whatever
+        // was selected, and is then handled as usual
+        if let Some(menu) = &mut self.menu {
+            match ev {
+                Tab => menu.select_next(),
+                BackTab => menu.select_previous(),
+                Enter => {
+                    self.close_menu();
+                    return Step::Continue;
+                }
+                Escape => {
+                    self.buffer
+                        .replace_before_cursor(menu.start, &menu.original);
+                    self.menu = None;
+                    return Step::Continue;
+                }
+                _ => self.close_menu(),
+            }
+
+            if let Some(menu) = &self.menu {
+                self.buffer
+                    .replace_before_cursor(menu.start, &menu.written());
+                return Step::Continue;
+            }
+        }
+
+        if self.vi.is_some()
+            && let Some(step) = self.handle_vi(&ev)
+        {
+            return step;
+        }
+
         match ev {
             Char(c) => self.buffer.insert(c),
             Backspace => self.buffer.backspace(),
             Delete => self.buffer.delete_forward(),
             Enter => return Step::Completed,
-            Tab => {
-                todo!()
-            }
-            Left => self.buffer.move_cursor_left(),
-            Right => self.buffer.move_cursor_right(),
-            Home => self.buffer.move_cursor_home(),
-            End => self.buffer.move_cursor_end(),
+            Tab => self.complete(),
+            BackTab | Escape => {}
+            Left | CtrlB => self.buffer.move_cursor_left(),
+            Right | CtrlF => self.buffer.move_cursor_right(),
+            Up => self.recall_older(),
+            Down => self.recall_newer(),
+            Home | CtrlA => self.buffer.move_cursor_home(),
+            End | CtrlE => self.buffer.move_cursor_end(),
             CtrlC => {
                 return Step::Aborted;
             }
+            CtrlG => {}
             CtrlJ => {
                 self.buffer.insert('\n');
                 return Step::Completed;
             }
+            CtrlR => self.search = Some(Search::default()),
+            AltB => self.buffer.move_word_left(),
+            AltF => self.buffer.move_word_right(),
+            CtrlT => self.buffer.transpose(),
+            CtrlK => {
+                let killed = self.buffer.kill_to_end();
+                self.kill(&killed, false, last);
+            }
+            AltD => {
+                let killed = self.buffer.kill_word_right();
+                self.kill(&killed, false, last);
+            }
+            CtrlU => {
+                let killed = self.buffer.kill_to_start();
+                self.kill(&killed, true, last);
+            }
+            CtrlW => {
+                let killed = self.buffer.kill_word_left();
+                self.kill(&killed, true, last);
+            }
+            CtrlY => self.yank(),
+            AltY => self.yank_pop(last),
         }
 
+        self.clamp_cursor();
         Step::Continue
     }
 
+    /// Saves killed text on the kill ring. A kill straight after
+    /// another joins it, so the lot yanks back as one.
+    fn kill(&mut self, text: &str, backward: bool, last: LastEdit) {
+        match last {
+            LastEdit::Kill if backward => self.kills.prepend(text),
+            LastEdit::Kill => self.kills.append(text),
+            _ => self.kills.push(text),
+        }
+        self.last = LastEdit::Kill;
+    }
+
+    fn yank(&mut self) {
+        let start = self.buffer.cursor_column();
+        match self.kills.yank() {
+            Some(text) => {
+                self.buffer.insert_str(text);
+                self.last = LastEdit::Yank { start };
+            }
+            None => self.notice = Some(Notice::Bell),
+        }
+    }
+
+    /// Swaps the text just yanked for the kill before it
+    fn yank_pop(&mut self, last: LastEdit) {
+        let LastEdit::Yank { start } = last else {
+            self.notice = Some(Notice::Bell);
+            return;
+        };
+
+        if let Some(text) = self.kills.rotate() {
+            self.buffer.replace_before_cursor(start, text);
+        }
+        self.last = last;
+    }
+
+    /// Handles `ev` while searching. Events the search doesn't use
+    /// accept the match and end it, and are then handled as usual.
+    fn handle_search(&mut self, ev: &EditorEvent) -> Option<Step> {
+        let search = self.search.as_mut()?;
+        let newest = self.history.len();
+
+        match ev {
+            Char(c) => {
+                search.query.push(*c);
+                // The entry showing may still match the longer query
+                let from = search.matched.map_or(newest, |i| i + 1);
+                search.find(&self.history, from);
+            }
+            Backspace => {
+                search.query.pop();
+                search.matched = None;
+                search.failed = false;
+                if !search.query.is_empty() {
+                    search.find(&self.history, newest);
+                }
+            }
+            CtrlR if !search.query.is_empty() => {
+                let from = search.matched.unwrap_or(newest);
+                search.find(&self.history, from);
+            }
+            CtrlR => {}
+            CtrlG => self.search = None,
+            Enter => {
+                self.accept_search();
+                return Some(Step::Completed);
+            }
+            _ => {
+                self.accept_search();
+                return None;
+            }
+        }
+
+        Some(Step::Continue)
+    }
+
+    /// Ends the search, putting any match in the line
+    fn accept_search(&mut self) {
+        if let Some(search) = self.search.take()
+            && let Some(entry) = search.matched.and_then(|i| self.history.get(i))
+        {
+            self.buffer.replace(entry);
+            self.recall = None;
+        }
+    }
+
+    /// Replaces the line with the entry before the one showing,
+    /// saving it as the draft first if it's the line being written
+    fn recall_older(&mut self) {
+        let index = match &self.recall {
+            Some(recall) => recall.index.checked_sub(1),
+            None => self.history.len().checked_sub(1),
+        };
+        let Some(index) = index else {
+            self.notice = Some(Notice::Bell);
+            return;
+        };
+
+        let recall = self.recall.get_or_insert_with(|| Recall {
+            index,
+            draft: self.buffer.as_display().to_string(),
+        });
+        recall.index = index;
+
+        let entry = self.history.get(index).unwrap_or_default();
+        self.buffer.replace(entry);
+    }
+
+    /// Replaces the line with the entry after the one showing, or
+    /// the draft after the newest
+    fn recall_newer(&mut self) {
+        let Some(recall) = &mut self.recall else {
+            self.notice = Some(Notice::Bell);
+            return;
+        };
+
+        recall.index += 1;
+        match self.history.get(recall.index) {
+            Some(entry) => self.buffer.replace(entry),
+            None => {
+                let draft = std::mem::take(&mut recall.draft);
+                self.buffer.replace(&draft);
+                self.recall = None;
+            }
+        }
+    }
+
+    /// Completes the word before the cursor with the completer for
+    /// the slot it is in. One candidate replaces it, several extend
+    /// it as far as they agree and open a menu.
+    fn complete(&mut self) {
+        let before = self.buffer.before_cursor();
+        let context = partial::context_at(&before, before.len());
+
+        let quote = match context.word.kind {
+            WordKind::Symbol => None,
+            WordKind::Literal {
+                quote,
+                terminated: false,
+            } => Some(quote),
+            WordKind::Literal {
+                terminated: true, ..
+            } => {
+                self.notice = Some(Notice::Bell);
+                return;
+            }
+        };
+
+        let Some(slot) = Slot::at(&context) else {
+            self.notice = Some(Notice::Bell);
+            return;
+        };
+
+        let word = context.word;
+        let start = before[..word.span.start].chars().count();
+        let original = before[word.span.start..].to_string();
+        let candidates = match self.completers.get(&slot) {
+            Some(completer) => completer.complete(&word),
+            None => vec![],
+        };
+
+        match candidates.as_slice() {
+            [] => self.notice = Some(Notice::Bell),
+            [only] => {
+                self.buffer
+                    .replace_before_cursor(start, &written(&only.apply(&word.text), quote));
+                self.accepted(slot, only);
+            }
+            _ => {
+                // Extending only makes sense when every candidate
+                // replaces the same part of the word
+                let span = candidates[0].span.clone();
+                if candidates.iter().all(|c| c.span == span) {
+                    let typed = &word.text[span.clone()];
+                    let trie: Trie = candidates.iter().map(|c| &c.text).collect();
+
+                    // Abbreviations don't start with what was typed, and
+                    // extending past one would drop some of it
+                    let prefix = if trie.count_completions(typed) == trie.count_completions("") {
+                        trie.longest_common_prefix(typed).unwrap_or_default()
+                    } else {
+                        String::new()
+                    };
+
+                    if prefix.chars().count() > typed.chars().count() {
+                        let extended = Completion {
+                            text: prefix,
+                            span,
+                            kind: candidates[0].kind,
+                            detail: None,
+                        };
+                        self.buffer.replace_before_cursor(
+                            start,
+                            &written(&extended.apply(&word.text), quote),
+                        );
+                    }
+                }
+
+                let menu = Menu {
+                    candidates,
+                    selected: None,
+                    slot,
+                    start,
+                    original,
+                    word: word.text,
+                    quote,
+                };
+
+                if menu.candidates.len() > LIST_WITHOUT_ASKING {
+                    self.notice = Some(Notice::Confirm(menu.candidates.len()));
+                    self.pending = Some(menu);
+                } else {
+                    self.menu = Some(menu);
+                }
+            }
+        }
+    }
+
+    /// Closes the menu, keeping what was selected
+    fn close_menu(&mut self) {
+        if let Some(menu) = self.menu.take()
+            && let Some(i) = menu.selected
+        {
+            self.accepted(menu.slot, &menu.candidates[i]);
+        }
+    }
+
+    fn accepted(&mut self, slot: Slot, completion: &Completion) {
+        if let Some(completer) = self.completers.get_mut(&slot) {
+            completer.accepted(completion);
+        }
+    }
+
     pub fn view(&self) -> EditorView<'_> {
         EditorView {
             text: self.buffer.as_display(),
             cursor_col: self.buffer.cursor_column(),
+            notice: self.notice.as_ref(),
+            mode: self.vi.as_ref().map(|vi| vi.mode),
+            search: self.search.as_ref().map(|search| {
+                let matched = search.matched.and_then(|i| self.history.get(i));
+                SearchView {
+                    query: &search.query,
+                    // After a failed step the entry holds an older,
+                    // shorter query, so it's shown without a highlight
+                    matched: matched.map(|entry| {
+                        let found = entry
+                            .get(search.at..)
+                            .filter(|rest| rest.starts_with(&search.query))
+                            .map(|_| search.at..search.at + search.query.len());
+                        (entry, found)
+                    }),
+                    failed: search.failed,
+                }
+            }),
         }
     }
 
+    pub fn menu(&self) -> Option<MenuView<'_>> {
+        self.menu.as_ref().map(|menu| MenuView {
+            candidates: &menu.candidates,
+            selected: menu.selected,
+        })
+    }
+
+    /// Takes the finished line, recording it in history
     pub fn take(&mut self) -> String {
-        self.buffer.take_string()
+        self.recall = None;
+        let line = self.buffer.take_string();
+
+        // Failing to save history isn't worth failing the line over,
+        // and the entry is still kept for this session
+        let _ = self.history.push(&line);
+        line
     }
 
     pub fn reset(&mut self) {
-        self.buffer.clear()
+        self.buffer.clear();
+        self.notice = None;
+        self.menu = None;
+        self.pending = None;
+        self.recall = None;
+        self.search = None;
+        self.last = LastEdit::Other;
+        if let Some(vi) = &mut self.vi {
+            vi.reset();
+        }
+    }
+}
+
+/// `text` as it is written where it's going - escaped as a symbol,
+/// or after the opening `quote` of a literal
+fn written(text: &str, quote: Option<char>) -> String {
+    match quote {
+        Some(quote) => format!("{quote}{}", escape::escape_literal(text)),
+        None => escape::escape(text),
     }
 }
 
 pub struct EditorView<'a> {
     pub text: BufferDisplay<'a>,
     pub cursor_col: usize,
+    pub notice: Option<&'a Notice>,
+    /// The vi mode, when editing the vi way
+    pub mode: Option<ViMode>,
+    /// Shown in place of the line while searching history
+    pub search: Option<SearchView<'a>>,
+}
+
+pub struct SearchView<'a> {
+    pub query: &'a str,
+    /// The entry found, and where in it the query is when it still
+    /// holds all of it
+    pub matched: Option<(&'a str, Option<Range<usize>>)>,
+    pub failed: bool,
+}
+
+pub struct MenuView<'a> {
+    pub candidates: &'a [Completion],
+    pub selected: Option<usize>,
 }
 
 #[cfg(test)]
@@ -1,6 +1,11 @@
 #[cfg(test)]
-mod tests {
-    use crate::core::{EditorCore, EditorEvent, Step};
+mod core_tests {
+    use crate::{
+        autocomplete::{Completer, Completion, Kind, slot::Slot},
+        core::{EditorCore, EditorEvent, Notice, Step, ViMode},
+        history::History,
+    };
+    use nash_parser::partial::Word;
 
     use EditorEvent::*;
 
@@ -8,6 +13,31 @@ mod tests {
         core.view().text.to_string()
     }
 
+    struct Words(Vec<String>);
+
+    impl Completer for Words {
+        fn complete(&self, word: &Word) -> Vec<Completion> {
+            self.0
+                .iter()
+                .filter(|w| w.starts_with(&word.text))
+                .map(|w| Completion::word(Kind::Executable, word, w.as_str()))
+                .collect()
+        }
+    }
+
+    fn completing(words: &[&str]) -> EditorCore {
+        EditorCore::new().with_completer(
+            Slot::Command,
+            Words(words.iter().map(|w| w.to_string()).collect()),
+        )
+    }
+
+    fn type_str(core: &mut EditorCore, s: &str) {
+        for c in s.chars() {
+            core.handle(Char(c));
+        }
+    }
+
     #[test]
     fn starts_empty() {
         let core = EditorCore::new();
@@ -160,4 +190,800 @@ mod tests {
         assert_eq!(core.take(), "two");
         assert_eq!(buf(&core), "");
     }
+
+    #[test]
+    fn tab_without_completer_rings_bell() {
+        let mut core = EditorCore::new();
+        type_str(&mut core, "(ls");
+
+        assert_eq!(core.handle(Tab), Step::Continue);
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+        assert_eq!(buf(&core), "(ls");
+    }
+
+    #[test]
+    fn tab_with_one_match_replaces_word() {
+        let mut core = completing(&["git", "grep"]);
+        type_str(&mut core, "(gi");
+        core.handle(Tab);
+
+        assert_eq!(buf(&core), "(git");
+        assert_eq!(core.view().notice, None);
+    }
+
+    #[test]
+    fn tab_keeps_text_after_cursor() {
+        let mut core = completing(&["git"]);
+        type_str(&mut core, "(gi)");
+        core.handle(Left);
+        core.handle(Tab);
+
+        assert_eq!(buf(&core), "(git)");
+        assert_eq!(core.view().cursor_col, 4);
+    }
+
+    fn menu_items(core: &EditorCore) -> Option<(Vec<String>, Option<usize>)> {
+        core.menu().map(|menu| {
+            let texts = menu.candidates.iter().map(|c| c.text.clone()).collect();
+            (texts, menu.selected)
+        })
+    }
+
+    #[test]
+    fn tab_with_several_matches_opens_menu() {
+        let mut core = completing(&["grep", "groups", "git"]);
+        type_str(&mut core, "(gr");
+        core.handle(Tab);
+
+        assert_eq!(
+            menu_items(&core),
+            Some((vec!["grep".to_string(), "groups".to_string()], None))
+        );
+        assert_eq!(buf(&core), "(gr");
+    }
+
+    #[test]
+    fn tab_cycles_through_menu() {
+        let mut core = completing(&["grep", "groups", "git"]);
+        type_str(&mut core, "(g");
+        core.handle(Tab);
+
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(grep");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(groups");
+        core.handle(Tab);
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(grep");
+        assert_eq!(menu_items(&core).unwrap().1, Some(0));
+
+        core.handle(BackTab);
+        assert_eq!(buf(&core), "(git");
+    }
+
+    #[test]
+    fn back_tab_starts_from_the_end() {
+        let mut core = completing(&["grep", "groups"]);
+        type_str(&mut core, "(g");
+        core.handle(Tab);
+        core.handle(BackTab);
+        assert_eq!(buf(&core), "(groups");
+    }
+
+    #[test]
+    fn enter_accepts_menu_selection() {
+        let mut core = completing(&["grep", "groups"]);
+        type_str(&mut core, "(g");
+        core.handle(Tab);
+        core.handle(Tab);
+
+        assert_eq!(core.handle(Enter), Step::Continue);
+        assert!(core.menu().is_none());
+        assert_eq!(buf(&core), "(grep");
+        assert_eq!(core.handle(Enter), Step::Completed);
+    }
+
+    #[test]
+    fn escape_restores_original_word() {
+        let mut core = completing(&["groupadd", "groups"]);
+        type_str(&mut core, "(g");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(group");
+        core.handle(Tab);
+
+        core.handle(Escape);
+        assert!(core.menu().is_none());
+        assert_eq!(buf(&core), "(g");
+    }
+
+    #[test]
+    fn typing_closes_menu_and_keeps_selection() {
+        let mut core = completing(&["grep", "groups"]);
+        type_str(&mut core, "(g");
+        core.handle(Tab);
+        core.handle(Tab);
+        core.handle(Char(' '));
+
+        assert!(core.menu().is_none());
+        assert_eq!(buf(&core), "(grep ");
+    }
+
+    #[test]
+    fn tab_extends_to_common_prefix() {
+        let mut core = completing(&["groupadd", "groups"]);
+        type_str(&mut core, "(g");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(group");
+    }
+
+    #[test]
+    fn tab_extends_keeping_only_agreed_case() {
+        /// Matches ignoring case, like the real completers
+        struct Caseless(Vec<&'static str>);
+
+        impl Completer for Caseless {
+            fn complete(&self, word: &Word) -> Vec<Completion> {
+                let typed = word.text.to_lowercase();
+                self.0
+                    .iter()
+                    .filter(|w| w.to_lowercase().starts_with(&typed))
+                    .map(|w| Completion::word(Kind::Executable, word, *w))
+                    .collect()
+            }
+        }
+
+        let completing = |words| EditorCore::new().with_completer(Slot::Command, Caseless(words));
+
+        let mut core = completing(vec!["Git", "git-lfs"]);
+        type_str(&mut core, "(g");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(git");
+
+        let mut core = completing(vec!["Makefile", "MANIFEST"]);
+        type_str(&mut core, "(m");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(Ma");
+    }
+
+    #[test]
+    fn tab_keeps_words_abbreviations_dont_start_with() {
+        /// Offers the same names whatever was typed
+        struct Abbreviations(Vec<&'static str>);
+
+        impl Completer for Abbreviations {
+            fn complete(&self, word: &Word) -> Vec<Completion> {
+                self.0
+                    .iter()
+                    .map(|w| Completion::word(Kind::Executable, word, *w))
+                    .collect()
+            }
+        }
+
+        let completing =
+            |words| EditorCore::new().with_completer(Slot::Command, Abbreviations(words));
+
+        let mut core = completing(vec!["gitk", "git-gk"]);
+        type_str(&mut core, "(gk");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(gk");
+        assert_eq!(
+            menu_items(&core),
+            Some((vec!["gitk".to_string(), "git-gk".to_string()], None))
+        );
+
+        let mut core = completing(vec!["gk-tool", "gk-view", "gitk"]);
+        type_str(&mut core, "(gk");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(gk");
+    }
+
+    #[test]
+    fn tab_in_literal_rings_bell() {
+        let mut core = completing(&["git"]);
+        type_str(&mut core, "(echo 'g");
+        core.handle(Tab);
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+    }
+
+    fn completing_paths(paths: &[&str]) -> EditorCore {
+        completing(&["cat"]).with_completer(
+            Slot::Argument,
+            Words(paths.iter().map(|p| p.to_string()).collect()),
+        )
+    }
+
+    #[test]
+    fn arguments_complete_paths() {
+        let mut core = completing_paths(&["Cargo.toml", "cat"]);
+        type_str(&mut core, "(cat Ca");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(cat Cargo.toml");
+
+        type_str(&mut core, " (c");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(cat Cargo.toml (cat");
+    }
+
+    #[test]
+    fn completed_paths_are_escaped() {
+        let mut core = completing_paths(&["my file (1).txt"]);
+        type_str(&mut core, "(cat my");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(cat my\\ file\\ \\(1\\).txt");
+    }
+
+    #[test]
+    fn escaped_words_are_completed_as_read() {
+        let mut core = completing_paths(&["my file (1).txt", "my file (2).txt"]);
+        type_str(&mut core, "(cat my\\ f");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(cat my\\ file\\ \\(");
+
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(cat my\\ file\\ \\(1\\).txt");
+        core.handle(Escape);
+        assert_eq!(buf(&core), "(cat my\\ f");
+    }
+
+    #[test]
+    fn literals_complete_without_escapes() {
+        let mut core = completing_paths(&["my file (1).txt"]);
+        type_str(&mut core, "(cat 'my");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(cat 'my file (1).txt");
+    }
+
+    #[test]
+    fn each_slot_has_its_own_completer() {
+        let mut core =
+            completing(&["ls"]).with_completer(Slot::File, Words(vec!["out.txt".into()]));
+        type_str(&mut core, "(out l");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(out ls");
+
+        type_str(&mut core, " o");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(out ls out.txt");
+    }
+
+    #[test]
+    fn list_operands_ring_bell() {
+        let mut core = completing(&["grep"]);
+        type_str(&mut core, "(pipe (ls) g");
+        core.handle(Tab);
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+        assert_eq!(buf(&core), "(pipe (ls) g");
+    }
+
+    /// Completes the name after the last `/`, like paths
+    struct Names(Vec<&'static str>);
+
+    impl Completer for Names {
+        fn complete(&self, word: &Word) -> Vec<Completion> {
+            let start = word.text.rfind('/').map_or(0, |i| i + 1);
+            self.0
+                .iter()
+                .filter(|name| name.starts_with(&word.text[start..]))
+                .map(|name| Completion {
+                    text: name.to_string(),
+                    span: start..word.text.len(),
+                    kind: Kind::Path,
+                    detail: None,
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn completions_replace_their_span() {
+        let mut core =
+            completing(&["cat"]).with_completer(Slot::Argument, Names(vec!["main.rs", "mod.rs"]));
+        type_str(&mut core, "(cat my\\ src/m");
+        core.handle(Tab);
+        assert_eq!(
+            menu_items(&core),
+            Some((vec!["main.rs".to_string(), "mod.rs".to_string()], None))
+        );
+
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(cat my\\ src/main.rs");
+        core.handle(Escape);
+        assert_eq!(buf(&core), "(cat my\\ src/m");
+    }
+
+    #[test]
+    fn many_matches_ask_first() {
+        let words: Vec<String> = (0..40).map(|i| format!("cmd{i}")).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        let mut core = completing(&words);
+        type_str(&mut core, "(c");
+        core.handle(Tab);
+        assert_eq!(buf(&core), "(cmd");
+        assert_eq!(core.view().notice, Some(&Notice::Confirm(40)));
+
+        // the answer is not typed into the line
+        core.handle(Char('y'));
+        assert_eq!(buf(&core), "(cmd");
+        assert_eq!(menu_items(&core).map(|(c, _)| c.len()), Some(40));
+
+        core.handle(Escape);
+        core.handle(Tab);
+        core.handle(Char('n'));
+        assert_eq!(buf(&core), "(cmd");
+        assert!(core.menu().is_none());
+    }
+
+    #[test]
+    fn accepted_completions_are_reported() {
+        use std::{cell::RefCell, rc::Rc};
+
+        struct Recording(Rc<RefCell<Vec<String>>>);
+
+        impl Completer for Recording {
+            fn complete(&self, word: &Word) -> Vec<Completion> {
+                ["grep", "groups", "git"]
+                    .iter()
+                    .filter(|w| w.starts_with(&word.text))
+                    .map(|w| Completion::word(Kind::Executable, word, *w))
+                    .collect()
+            }
+
+            fn accepted(&mut self, completion: &Completion) {
+                self.0.borrow_mut().push(completion.text.clone());
+            }
+        }
+
+        let accepted = Rc::new(RefCell::new(vec![]));
+        let mut core =
+            EditorCore::new().with_completer(Slot::Command, Recording(Rc::clone(&accepted)));
+
+        type_str(&mut core, "(gi");
+        core.handle(Tab);
+        core.handle(Char(' '));
+        type_str(&mut core, "(gr");
+        core.handle(Tab);
+        core.handle(Tab);
+        core.handle(Enter);
+        core.handle(Char(' '));
+        type_str(&mut core, "(gr");
+        core.handle(Tab);
+        core.handle(Tab);
+        core.handle(Escape);
+
+        assert_eq!(*accepted.borrow(), vec!["git", "grep"]);
+    }
+
+    fn with_history(lines: &[&str]) -> EditorCore {
+        let mut history = History::new();
+        for line in lines {
+            history.push(line).unwrap();
+        }
+        EditorCore::new().with_history(history)
+    }
+
+    #[test]
+    fn up_and_down_walk_history() {
+        let mut core = with_history(&["(ls)", "(pwd)"]);
+
+        core.handle(Up);
+        assert_eq!(buf(&core), "(pwd)");
+        core.handle(Up);
+        assert_eq!(buf(&core), "(ls)");
+        assert_eq!(core.view().cursor_col, 4);
+
+        core.handle(Up);
+        assert_eq!(buf(&core), "(ls)");
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+
+        core.handle(Down);
+        assert_eq!(buf(&core), "(pwd)");
+    }
+
+    #[test]
+    fn down_past_newest_restores_draft() {
+        let mut core = with_history(&["(ls)"]);
+        type_str(&mut core, "(ech");
+
+        core.handle(Up);
+        assert_eq!(buf(&core), "(ls)");
+        core.handle(Down);
+        assert_eq!(buf(&core), "(ech");
+
+        core.handle(Down);
+        assert_eq!(buf(&core), "(ech");
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+    }
+
+    #[test]
+    fn taken_lines_are_recorded() {
+        let mut core = EditorCore::new();
+        type_str(&mut core, "(ls)");
+        core.handle(Enter);
+        core.take();
+
+        type_str(&mut core, "(ls)");
+        core.handle(Enter);
+        core.take();
+
+        core.handle(Up);
+        assert_eq!(buf(&core), "(ls)");
+        core.handle(Up);
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+    }
+
+    #[test]
+    fn empty_history_rings_bell() {
+        let mut core = EditorCore::new();
+        core.handle(Up);
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+        assert_eq!(buf(&core), "");
+    }
+
+    /// The query, the entry matched and whether the search failed
+    fn searching(core: &EditorCore) -> Option<(String, Option<String>, bool)> {
+        core.view().search.map(|search| {
+            let matched = search.matched.map(|(entry, _)| entry.to_string());
+            (search.query.to_string(), matched, search.failed)
+        })
+    }
+
+    #[test]
+    fn ctrl_r_finds_newest_match() {
+        let mut core = with_history(&["(git status)", "(ls)", "(git log)"]);
+        type_str(&mut core, "(draft");
+        core.handle(CtrlR);
+        type_str(&mut core, "git");
+
+        assert_eq!(
+            searching(&core),
+            Some(("git".to_string(), Some("(git log)".to_string()), false))
+        );
+        assert_eq!(buf(&core), "(draft");
+
+        let view = core.view();
+        let (_, found) = view.search.unwrap().matched.unwrap();
+        assert_eq!(found, Some(1..4));
+    }
+
+    #[test]
+    fn failed_step_shows_the_match_without_highlight() {
+        let mut core = with_history(&["xy"]);
+        core.handle(CtrlR);
+        type_str(&mut core, "xyz");
+
+        let view = core.view();
+        let search = view.search.unwrap();
+        assert!(search.failed);
+        assert_eq!(search.matched, Some(("xy", None)));
+    }
+
+    #[test]
+    fn ctrl_r_steps_to_older_matches() {
+        let mut core = with_history(&["(git status)", "(ls)", "(git log)"]);
+        core.handle(CtrlR);
+        type_str(&mut core, "git");
+        core.handle(CtrlR);
+        assert_eq!(searching(&core).unwrap().1.as_deref(), Some("(git status)"));
+
+        // Nothing older, so the match stays and the search fails
+        core.handle(CtrlR);
+        assert_eq!(
+            searching(&core),
+            Some(("git".to_string(), Some("(git status)".to_string()), true))
+        );
+    }
+
+    #[test]
+    fn typing_narrows_from_the_current_match() {
+        let mut core = with_history(&["(git stash)", "(git status)", "(git log)"]);
+        core.handle(CtrlR);
+        type_str(&mut core, "git st");
+        assert_eq!(searching(&core).unwrap().1.as_deref(), Some("(git status)"));
+
+        type_str(&mut core, "as");
+        assert_eq!(searching(&core).unwrap().1.as_deref(), Some("(git stash)"));
+
+        core.handle(Backspace);
+        core.handle(Backspace);
+        assert_eq!(searching(&core).unwrap().1.as_deref(), Some("(git status)"));
+    }
+
+    #[test]
+    fn enter_runs_the_match() {
+        let mut core = with_history(&["(ls -la)", "(pwd)"]);
+        core.handle(CtrlR);
+        type_str(&mut core, "ls");
+
+        assert_eq!(core.handle(Enter), Step::Completed);
+        assert!(searching(&core).is_none());
+        assert_eq!(core.take(), "(ls -la)");
+    }
+
+    #[test]
+    fn ctrl_g_cancels_search() {
+        let mut core = with_history(&["(ls -la)"]);
+        type_str(&mut core, "(draft");
+        core.handle(CtrlR);
+        type_str(&mut core, "ls");
+        core.handle(CtrlG);
+
+        assert!(searching(&core).is_none());
+        assert_eq!(buf(&core), "(draft");
+    }
+
+    #[test]
+    fn other_keys_accept_and_edit_the_match() {
+        let mut core = with_history(&["(ls -la)"]);
+        core.handle(CtrlR);
+        type_str(&mut core, "ls");
+        core.handle(Left);
+
+        assert!(searching(&core).is_none());
+        assert_eq!(buf(&core), "(ls -la)");
+        assert_eq!(core.view().cursor_col, 7);
+    }
+
+    #[test]
+    fn emacs_movement() {
+        let mut core = EditorCore::new();
+        type_str(&mut core, "(git (log -n 3))");
+
+        core.handle(AltB);
+        assert_eq!(core.view().cursor_col, 13);
+        core.handle(CtrlA);
+        assert_eq!(core.view().cursor_col, 0);
+        core.handle(AltF);
+        assert_eq!(core.view().cursor_col, 4);
+        core.handle(CtrlF);
+        core.handle(CtrlB);
+        assert_eq!(core.view().cursor_col, 4);
+        core.handle(CtrlE);
+        assert_eq!(core.view().cursor_col, 16);
+    }
+
+    #[test]
+    fn killed_text_yanks_back() {
+        let mut core = EditorCore::new();
+        type_str(&mut core, "(ls -la)");
+        core.handle(CtrlB);
+
+        core.handle(CtrlW);
+        assert_eq!(buf(&core), "(ls )");
+        core.handle(CtrlE);
+        core.handle(CtrlY);
+        assert_eq!(buf(&core), "(ls )-la");
+    }
+
+    #[test]
+    fn consecutive_kills_join() {
+        let mut core = EditorCore::new();
+        type_str(&mut core, "(git log -n 3)");
+        core.handle(CtrlB);
+
+        core.handle(CtrlW);
+        core.handle(CtrlW);
+        assert_eq!(buf(&core), "(git log )");
+        core.handle(CtrlY);
+        assert_eq!(buf(&core), "(git log -n 3)");
+
+        core.handle(CtrlA);
+        core.handle(CtrlF);
+        core.handle(AltD);
+        core.handle(AltD);
+        assert_eq!(buf(&core), "( -n 3)");
+        core.handle(CtrlE);
+        core.handle(CtrlY);
+        assert_eq!(buf(&core), "( -n 3)git log");
+    }
+
+    #[test]
+    fn yank_pop_cycles_through_kills() {
+        let mut core = EditorCore::new();
+        type_str(&mut core, "one");
+        core.handle(CtrlU);
+        type_str(&mut core, "two");
+        core.handle(CtrlU);
+
+        core.handle(AltY);
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+
+        type_str(&mut core, "(");
+        core.handle(CtrlY);
+        assert_eq!(buf(&core), "(two");
+        core.handle(AltY);
+        assert_eq!(buf(&core), "(one");
+        core.handle(AltY);
+        assert_eq!(buf(&core), "(two");
+
+        // Only straight after a yank
+        type_str(&mut core, " ");
+        core.handle(AltY);
+        assert_eq!(buf(&core), "(two ");
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+    }
+
+    #[test]
+    fn yank_with_nothing_killed_rings_bell() {
+        let mut core = EditorCore::new();
+        core.handle(CtrlK);
+        core.handle(CtrlY);
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+    }
+
+    #[test]
+    fn ctrl_t_transposes() {
+        let mut core = EditorCore::new();
+        type_str(&mut core, "(sl");
+        core.handle(CtrlT);
+        assert_eq!(buf(&core), "(ls");
+    }
+
+    /// A vi mode core holding `line`, in normal mode with the cursor
+    /// on its last char
+    fn vi(line: &str) -> EditorCore {
+        let mut core = EditorCore::new().with_vi_mode();
+        type_str(&mut core, line);
+        core.handle(Escape);
+        core
+    }
+
+    #[test]
+    fn vi_starts_in_insert_mode() {
+        let mut core = EditorCore::new().with_vi_mode();
+        assert_eq!(core.view().mode, Some(ViMode::Insert));
+
+        type_str(&mut core, "(ls)");
+        core.handle(Escape);
+        assert_eq!(core.view().mode, Some(ViMode::Normal));
+        assert_eq!(core.view().cursor_col, 3);
+
+        core.handle(Enter);
+        assert_eq!(core.take(), "(ls)");
+        core.reset();
+        assert_eq!(core.view().mode, Some(ViMode::Insert));
+
+        assert_eq!(EditorCore::new().view().mode, None);
+    }
+
+    #[test]
+    fn vi_motions() {
+        let mut core = vi("(git log -n 3)");
+
+        type_str(&mut core, "0");
+        assert_eq!(core.view().cursor_col, 0);
+        type_str(&mut core, "w");
+        assert_eq!(core.view().cursor_col, 1);
+        type_str(&mut core, "2w");
+        assert_eq!(core.view().cursor_col, 9);
+        type_str(&mut core, "b");
+        assert_eq!(core.view().cursor_col, 5);
+        type_str(&mut core, "e");
+        assert_eq!(core.view().cursor_col, 7);
+        type_str(&mut core, "3h");
+        assert_eq!(core.view().cursor_col, 4);
+        type_str(&mut core, "l$");
+        assert_eq!(core.view().cursor_col, 13);
+
+        // Never past the last char
+        type_str(&mut core, "l");
+        assert_eq!(core.view().cursor_col, 13);
+    }
+
+    #[test]
+    fn vi_percent_jumps_between_parens() {
+        let mut core = vi("(echo ')' (pwd))");
+
+        type_str(&mut core, "%");
+        assert_eq!(core.view().cursor_col, 0);
+        type_str(&mut core, "%");
+        assert_eq!(core.view().cursor_col, 15);
+
+        type_str(&mut core, "0w%");
+        assert_eq!(core.view().cursor_col, 14);
+    }
+
+    #[test]
+    fn vi_operators_take_motions() {
+        let mut core = vi("(git log -n 3)");
+        type_str(&mut core, "0wdw");
+        assert_eq!(buf(&core), "(log -n 3)");
+
+        type_str(&mut core, "d2e");
+        assert_eq!(buf(&core), "( 3)");
+
+        type_str(&mut core, "$d%");
+        assert_eq!(buf(&core), "");
+    }
+
+    #[test]
+    fn vi_change_inserts() {
+        let mut core = vi("(git log)");
+        type_str(&mut core, "0wcw");
+        assert_eq!(core.view().mode, Some(ViMode::Insert));
+        assert_eq!(buf(&core), "( log)");
+
+        type_str(&mut core, "jj");
+        core.handle(Escape);
+        assert_eq!(buf(&core), "(jj log)");
+
+        type_str(&mut core, "cc");
+        assert_eq!(buf(&core), "");
+    }
+
+    #[test]
+    fn vi_yank_and_put() {
+        let mut core = vi("(ls -la)");
+        type_str(&mut core, "0wyw");
+        assert_eq!(core.view().cursor_col, 1);
+        type_str(&mut core, "P");
+        assert_eq!(buf(&core), "(ls ls -la)");
+        assert_eq!(core.view().cursor_col, 3);
+
+        type_str(&mut core, "$x");
+        assert_eq!(buf(&core), "(ls ls -la");
+        type_str(&mut core, "0p");
+        assert_eq!(buf(&core), "()ls ls -la");
+    }
+
+    #[test]
+    fn vi_dot_repeats_changes() {
+        let mut core = vi("(a b c d)");
+        type_str(&mut core, "0wdw.");
+        assert_eq!(buf(&core), "(c d)");
+
+        type_str(&mut core, "cwx");
+        core.handle(Escape);
+        type_str(&mut core, "w.");
+        assert_eq!(buf(&core), "(x x)");
+
+        // A count replaces the one the change had
+        type_str(&mut core, "0x2.");
+        assert_eq!(buf(&core), "x)");
+    }
+
+    #[test]
+    fn vi_insert_counts_repeat_the_text() {
+        let mut core = vi("(ls)");
+        type_str(&mut core, "3ix");
+        core.handle(Escape);
+        assert_eq!(buf(&core), "(lsxxx)");
+        assert_eq!(core.view().cursor_col, 5);
+
+        type_str(&mut core, "2A!");
+        core.handle(Escape);
+        assert_eq!(buf(&core), "(lsxxx)!!");
+
+        // `.` types it its own count of times, or the one given
+        type_str(&mut core, "0.");
+        assert_eq!(buf(&core), "(lsxxx)!!!!");
+        type_str(&mut core, "3.");
+        assert_eq!(buf(&core), "(lsxxx)!!!!!!!");
+    }
+
+    #[test]
+    fn vi_backspace_past_the_insert_keeps_dot_text() {
+        let mut core = vi("(ls -la)");
+        type_str(&mut core, "bia");
+        core.handle(Backspace);
+        core.handle(Backspace);
+        core.handle(Backspace);
+        type_str(&mut core, "xy");
+        core.handle(Escape);
+        assert_eq!(buf(&core), "(lxy-la)");
+
+        type_str(&mut core, "0.");
+        assert_eq!(buf(&core), "xy(lxy-la)");
+    }
+
+    #[test]
+    fn vi_unknown_keys_ring_bell() {
+        let mut core = vi("(ls)");
+        type_str(&mut core, "dz");
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+        assert_eq!(buf(&core), "(ls)");
+
+        type_str(&mut core, ".");
+        assert_eq!(core.view().notice, Some(&Notice::Bell));
+    }
 }
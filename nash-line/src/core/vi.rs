@@ -0,0 +1,655 @@
+//! Vi editing mode
+//!
+//! Lines start in insert mode, where keys behave as they always do.
+//! Escape switches to normal mode, where keys are read as commands:
+//! an optional count, then a motion, an operator and the motion it
+//! acts on, or one of the commands that go back to inserting.
+//!
+//! Words follow vi - a run of word chars, or a run of anything else
+//! that isn't blank - so `(git` is two words. `%` only jumps between
+//! parens the lexer reads as parens, skipping those in literals.
+
+use std::ops::Range;
+
+use nash_parser::lexer::{self, Token};
+
+use super::{EditorCore, EditorEvent, Notice, Step};
+use crate::buffer::is_word_char;
+
+/// Counts past this are taken as this. No line is long enough to
+/// need more, and `p` shouldn't fill memory.
+const MAX_COUNT: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViMode {
+    Insert,
+    Normal,
+}
+
+#[derive(Debug)]
+pub(super) struct Vi {
+    pub(super) mode: ViMode,
+    /// Keys typed so far towards a normal mode command
+    keys: String,
+    /// The last command that changed the line, for `.`
+    last_change: Option<Change>,
+    /// A change whose insert is still being typed, and what has
+    /// been typed
+    inserting: Option<Change>,
+    /// Where that insert started. Backspacing past it deletes text
+    /// the insert didn't type.
+    insert_start: usize,
+}
+
+/// A command that changed the line, with the text it inserted
+#[derive(Debug, Clone)]
+struct Change {
+    command: Command,
+    inserted: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Command {
+    count: Option<usize>,
+    action: Action,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Move(Motion),
+    Operate(Operator, Motion),
+    /// `dd`, `cc` and `yy`, which act on the whole line
+    OperateLine(Operator),
+    Insert(Entry),
+    /// `p`, or `P` before the cursor
+    Put {
+        after: bool,
+    },
+    /// `k` and `j`, walking history
+    Older,
+    Newer,
+    Repeat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Motion {
+    Left,
+    Right,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    LineEnd,
+    MatchingParen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+/// Where `i`, `a`, `I` and `A` start inserting
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Entry {
+    Before,
+    After,
+    LineStart,
+    LineEnd,
+}
+
+#[derive(Debug, PartialEq)]
+enum Parse {
+    /// The keys so far start a command
+    Incomplete,
+    Invalid,
+    Done(Command),
+}
+
+impl Vi {
+    pub(super) fn new() -> Self {
+        Vi {
+            mode: ViMode::Insert,
+            keys: String::new(),
+            last_change: None,
+            inserting: None,
+            insert_start: 0,
+        }
+    }
+
+    /// Back to inserting, for a new line
+    pub(super) fn reset(&mut self) {
+        self.mode = ViMode::Insert;
+        self.keys.clear();
+        self.inserting = None;
+    }
+}
+
+impl Motion {
+    fn from_key(key: char) -> Option<Motion> {
+        Some(match key {
+            'h' => Motion::Left,
+            'l' | ' ' => Motion::Right,
+            'w' => Motion::WordForward,
+            'b' => Motion::WordBackward,
+            'e' => Motion::WordEnd,
+            '0' => Motion::LineStart,
+            '$' => Motion::LineEnd,
+            '%' => Motion::MatchingParen,
+            _ => return None,
+        })
+    }
+
+    /// Whether an operator acts on the char the motion lands on
+    fn inclusive(self) -> bool {
+        matches!(
+            self,
+            Motion::WordEnd | Motion::LineEnd | Motion::MatchingParen
+        )
+    }
+
+    /// Where the motion goes from `cursor`, if anywhere
+    fn target(self, chars: &[char], cursor: usize, count: usize) -> Option<usize> {
+        let repeat =
+            |step: fn(&[char], usize) -> usize| (0..count).fold(cursor, |at, _| step(chars, at));
+
+        Some(match self {
+            Motion::Left => cursor.saturating_sub(count),
+            Motion::Right => (cursor + count).min(chars.len()),
+            Motion::WordForward => repeat(next_word_start),
+            Motion::WordBackward => repeat(previous_word_start),
+            Motion::WordEnd => repeat(word_end),
+            Motion::LineStart => 0,
+            Motion::LineEnd => chars.len().saturating_sub(1),
+            Motion::MatchingParen => matching_paren(chars, cursor)?,
+        })
+    }
+}
+
+/// Reads the keys typed so far as a command
+fn parse(keys: &str) -> Parse {
+    let mut keys = keys.chars().peekable();
+    let count = take_count(&mut keys);
+
+    let Some(key) = keys.next() else {
+        return Parse::Incomplete;
+    };
+
+    let (count, action) = match key {
+        'i' => (count, Action::Insert(Entry::Before)),
+        'a' => (count, Action::Insert(Entry::After)),
+        'I' => (count, Action::Insert(Entry::LineStart)),
+        'A' => (count, Action::Insert(Entry::LineEnd)),
+        'x' => (count, Action::Operate(Operator::Delete, Motion::Right)),
+        'D' => (count, Action::Operate(Operator::Delete, Motion::LineEnd)),
+        'C' => (count, Action::Operate(Operator::Change, Motion::LineEnd)),
+        'p' => (count, Action::Put { after: true }),
+        'P' => (count, Action::Put { after: false }),
+        'k' => (count, Action::Older),
+        'j' => (count, Action::Newer),
+        '.' => (count, Action::Repeat),
+        'd' | 'c' | 'y' => {
+            let operator = match key {
+                'd' => Operator::Delete,
+                'c' => Operator::Change,
+                _ => Operator::Yank,
+            };
+
+            // `2d3w` deletes six words
+            let motion_count = take_count(&mut keys);
+            let count = match (count, motion_count) {
+                (Some(a), Some(b)) => Some(a.saturating_mul(b)),
+                (a, b) => a.or(b),
+            };
+
+            match keys.next() {
+                None => return Parse::Incomplete,
+                Some(k) if k == key => (count, Action::OperateLine(operator)),
+                Some(k) => match Motion::from_key(k) {
+                    Some(motion) => (count, Action::Operate(operator, motion)),
+                    None => return Parse::Invalid,
+                },
+            }
+        }
+        key => match Motion::from_key(key) {
+            Some(motion) => (count, Action::Move(motion)),
+            None => return Parse::Invalid,
+        },
+    };
+
+    Parse::Done(Command { count, action })
+}
+
+/// Reads a count. A leading `0` is the motion, not a count.
+fn take_count(keys: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+    let mut count: Option<usize> = None;
+
+    while let Some(&digit) = keys.peek() {
+        match digit.to_digit(10) {
+            Some(0) if count.is_none() => break,
+            Some(d) => {
+                count = Some(
+                    count
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(d as usize),
+                );
+                keys.next();
+            }
+            None => break,
+        }
+    }
+
+    count
+}
+
+#[derive(PartialEq)]
+enum Class {
+    Blank,
+    Word,
+    Other,
+}
+
+fn class(c: char) -> Class {
+    if c.is_whitespace() {
+        Class::Blank
+    } else if is_word_char(c) {
+        Class::Word
+    } else {
+        Class::Other
+    }
+}
+
+/// Where the next word starts, or the end of the line
+fn next_word_start(chars: &[char], at: usize) -> usize {
+    let Some(&first) = chars.get(at) else {
+        return chars.len();
+    };
+
+    let mut i = at;
+    let first = class(first);
+    if first != Class::Blank {
+        while chars.get(i).is_some_and(|&c| class(c) == first) {
+            i += 1;
+        }
+    }
+    while chars.get(i).is_some_and(|&c| class(c) == Class::Blank) {
+        i += 1;
+    }
+
+    i
+}
+
+/// Where the word before `at` starts, or the word `at` is in
+fn previous_word_start(chars: &[char], at: usize) -> usize {
+    let mut i = at.min(chars.len());
+    while i > 0 && class(chars[i - 1]) == Class::Blank {
+        i -= 1;
+    }
+
+    let Some(&last) = i.checked_sub(1).and_then(|j| chars.get(j)) else {
+        return 0;
+    };
+    let last = class(last);
+    while i > 0 && class(chars[i - 1]) == last {
+        i -= 1;
+    }
+
+    i
+}
+
+/// The last char of the word after `at`, or of the word `at` is in
+fn word_end(chars: &[char], at: usize) -> usize {
+    let mut i = at + 1;
+    while chars.get(i).is_some_and(|&c| class(c) == Class::Blank) {
+        i += 1;
+    }
+
+    if i >= chars.len() {
+        return chars.len().saturating_sub(1);
+    }
+
+    end_of_word(chars, i)
+}
+
+/// The last char of the word `at` starts or is in
+fn end_of_word(chars: &[char], at: usize) -> usize {
+    let Some(&first) = chars.get(at) else {
+        return at;
+    };
+
+    let first = class(first);
+    let mut i = at;
+    while chars.get(i + 1).is_some_and(|&c| class(c) == first) {
+        i += 1;
+    }
+
+    i
+}
+
+/// The paren matching the one under the cursor, or the first one
+/// after it
+fn matching_paren(chars: &[char], cursor: usize) -> Option<usize> {
+    let text: String = chars.iter().collect();
+
+    // The lexer speaks in bytes
+    let column = |byte: usize| text[..byte].chars().count();
+    let parens: Vec<(usize, bool)> = lexer::scan(&text)
+        .tokens
+        .iter()
+        .filter_map(|t| match t.token {
+            Token::Open => Some((column(t.span.start), true)),
+            Token::Closed => Some((column(t.span.start), false)),
+            _ => None,
+        })
+        .collect();
+
+    let from = parens.iter().position(|&(col, _)| col >= cursor)?;
+    let (_, open) = parens[from];
+
+    let mut depth = 0;
+    let mut walk = |&(col, is_open): &(usize, bool)| {
+        depth += if is_open == open { 1 } else { -1 };
+        (depth == 0).then_some(col)
+    };
+
+    if open {
+        parens[from..].iter().find_map(&mut walk)
+    } else {
+        parens[..=from].iter().rev().find_map(&mut walk)
+    }
+}
+
+impl EditorCore {
+    /// Handles `ev` in vi mode. Events it doesn't use are handled
+    /// as usual - all of them in insert mode.
+    pub(super) fn handle_vi(&mut self, ev: &EditorEvent) -> Option<Step> {
+        let vi = self.vi.as_mut()?;
+
+        if vi.mode == ViMode::Insert {
+            match ev {
+                EditorEvent::Escape => {
+                    self.normal_mode();
+                    return Some(Step::Continue);
+                }
+                EditorEvent::Char(c) => {
+                    if let Some(change) = &mut vi.inserting {
+                        change.inserted.push(*c);
+                    }
+                }
+                EditorEvent::Backspace => {
+                    if let Some(change) = &mut vi.inserting
+                        && self.buffer.cursor_column() > vi.insert_start
+                    {
+                        change.inserted.pop();
+                    }
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        match ev {
+            EditorEvent::Char(c) => {
+                vi.keys.push(*c);
+                match parse(&vi.keys) {
+                    Parse::Incomplete => {}
+                    Parse::Invalid => {
+                        vi.keys.clear();
+                        self.notice = Some(Notice::Bell);
+                    }
+                    Parse::Done(command) => {
+                        vi.keys.clear();
+                        self.run(command);
+                    }
+                }
+            }
+            EditorEvent::Escape => vi.keys.clear(),
+            EditorEvent::Backspace => self.buffer.move_cursor_left(),
+            _ => {
+                vi.keys.clear();
+                return None;
+            }
+        }
+
+        Some(Step::Continue)
+    }
+
+    /// Leaves insert mode, keeping what was typed for `.`. An
+    /// insert given a count types it that many times over.
+    fn normal_mode(&mut self) {
+        let Some(vi) = &mut self.vi else {
+            return;
+        };
+
+        vi.mode = ViMode::Normal;
+        if let Some(change) = vi.inserting.take() {
+            if let Action::Insert(_) = change.command.action {
+                let count = change.command.count.unwrap_or(1).min(MAX_COUNT);
+                self.buffer.insert_str(&change.inserted.repeat(count - 1));
+            }
+            vi.last_change = Some(change);
+        }
+
+        self.buffer.move_cursor_left();
+    }
+
+    fn insert_mode(&mut self, command: Command) {
+        if let Some(vi) = &mut self.vi {
+            vi.mode = ViMode::Insert;
+            vi.insert_start = self.buffer.cursor_column();
+            vi.inserting = Some(Change {
+                command,
+                inserted: String::new(),
+            });
+        }
+    }
+
+    /// In normal mode the cursor sits on a char, never past the last
+    pub(super) fn clamp_cursor(&mut self) {
+        if self.vi.as_ref().is_some_and(|vi| vi.mode == ViMode::Normal) {
+            let last = self.buffer.len().saturating_sub(1);
+            if self.buffer.cursor_column() > last {
+                self.buffer.set_cursor(last);
+            }
+        }
+    }
+
+    fn run(&mut self, command: Command) {
+        let count = command.count.unwrap_or(1).min(MAX_COUNT);
+        let cursor = self.buffer.cursor_column();
+        let chars = self.buffer.chars();
+
+        match command.action {
+            Action::Move(motion) => match motion.target(&chars, cursor, count) {
+                Some(target) => self.buffer.set_cursor(target),
+                None => self.notice = Some(Notice::Bell),
+            },
+            Action::Operate(operator, motion) => {
+                // `cw` on a word changes to its end, like `ce`, even
+                // from the last char of it
+                let changes_word = operator == Operator::Change
+                    && motion == Motion::WordForward
+                    && chars.get(cursor).is_some_and(|&c| !c.is_whitespace());
+                let target = if changes_word {
+                    Some((1..count).fold(end_of_word(&chars, cursor), |at, _| word_end(&chars, at)))
+                } else {
+                    motion.target(&chars, cursor, count)
+                };
+                let Some(target) = target else {
+                    self.notice = Some(Notice::Bell);
+                    return;
+                };
+
+                let (start, end) = (cursor.min(target), cursor.max(target));
+                let end = if changes_word || motion.inclusive() {
+                    end + 1
+                } else {
+                    end
+                };
+                self.operate(operator, command, start..end.min(chars.len()));
+            }
+            Action::OperateLine(operator) => self.operate(operator, command, 0..chars.len()),
+            Action::Insert(entry) => {
+                let at = match entry {
+                    Entry::Before => cursor,
+                    Entry::After => (cursor + 1).min(chars.len()),
+                    Entry::LineStart => 0,
+                    Entry::LineEnd => chars.len(),
+                };
+                self.buffer.set_cursor(at);
+                self.insert_mode(command);
+            }
+            Action::Put { after } => {
+                let Some(text) = self.kills.yank() else {
+                    self.notice = Some(Notice::Bell);
+                    return;
+                };
+                let text = text.repeat(count);
+
+                if after && !chars.is_empty() {
+                    self.buffer.move_cursor_right();
+                }
+                self.buffer.insert_str(&text);
+                self.buffer.move_cursor_left();
+                self.changed(command);
+            }
+            Action::Older => (0..count).for_each(|_| self.recall_older()),
+            Action::Newer => (0..count).for_each(|_| self.recall_newer()),
+            Action::Repeat => self.repeat(command.count),
+        }
+
+        self.clamp_cursor();
+    }
+
+    fn operate(&mut self, operator: Operator, command: Command, range: Range<usize>) {
+        match operator {
+            Operator::Yank => {
+                let text: String = self.buffer.chars()[range.clone()].iter().collect();
+                self.kills.push(&text);
+                self.buffer.set_cursor(range.start);
+            }
+            Operator::Delete => {
+                let text = self.buffer.remove(range);
+                self.kills.push(&text);
+                self.changed(command);
+            }
+            Operator::Change => {
+                let text = self.buffer.remove(range);
+                self.kills.push(&text);
+                self.insert_mode(command);
+            }
+        }
+    }
+
+    /// Remembers a command that changed the line without inserting
+    fn changed(&mut self, command: Command) {
+        if let Some(vi) = &mut self.vi {
+            vi.last_change = Some(Change {
+                command,
+                inserted: String::new(),
+            });
+        }
+    }
+
+    /// Runs the last change again, with `count` in place of its own
+    /// if given, typing what it inserted
+    fn repeat(&mut self, count: Option<usize>) {
+        let Some(change) = self.vi.as_ref().and_then(|vi| vi.last_change.clone()) else {
+            self.notice = Some(Notice::Bell);
+            return;
+        };
+
+        let command = Command {
+            count: count.or(change.command.count),
+            ..change.command
+        };
+        self.run(command);
+
+        if self.vi.as_ref().is_some_and(|vi| vi.mode == ViMode::Insert) {
+            self.buffer.insert_str(&change.inserted);
+            if let Some(vi) = &mut self.vi {
+                vi.inserting = Some(Change { command, ..change });
+            }
+            self.normal_mode();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_parse_counts_and_operators() {
+        assert_eq!(parse("2"), Parse::Incomplete);
+        assert_eq!(parse("d"), Parse::Incomplete);
+        assert_eq!(
+            parse("2d3w"),
+            Parse::Done(Command {
+                count: Some(6),
+                action: Action::Operate(Operator::Delete, Motion::WordForward),
+            })
+        );
+        assert_eq!(
+            parse("10l"),
+            Parse::Done(Command {
+                count: Some(10),
+                action: Action::Move(Motion::Right),
+            })
+        );
+        assert_eq!(
+            parse("0"),
+            Parse::Done(Command {
+                count: None,
+                action: Action::Move(Motion::LineStart),
+            })
+        );
+        assert_eq!(
+            parse("cc"),
+            Parse::Done(Command {
+                count: None,
+                action: Action::OperateLine(Operator::Change),
+            })
+        );
+        assert_eq!(parse("dz"), Parse::Invalid);
+        assert_eq!(parse("Z"), Parse::Invalid);
+    }
+
+    #[test]
+    fn test_word_motions() {
+        let line = chars("(git log -n3)");
+
+        assert_eq!(next_word_start(&line, 0), 1);
+        assert_eq!(next_word_start(&line, 1), 5);
+        assert_eq!(next_word_start(&line, 9), 12);
+        assert_eq!(next_word_start(&line, 12), 13);
+
+        assert_eq!(previous_word_start(&line, 5), 1);
+        assert_eq!(previous_word_start(&line, 1), 0);
+        assert_eq!(previous_word_start(&line, 13), 12);
+
+        assert_eq!(word_end(&line, 0), 3);
+        assert_eq!(word_end(&line, 3), 7);
+        assert_eq!(word_end(&line, 11), 12);
+        assert_eq!(word_end(&line, 12), 12);
+    }
+
+    #[test]
+    fn test_matching_paren_skips_literals() {
+        let line = chars("(echo ')' (pwd))");
+
+        assert_eq!(matching_paren(&line, 0), Some(15));
+        assert_eq!(matching_paren(&line, 15), Some(0));
+        assert_eq!(matching_paren(&line, 10), Some(14));
+        // The next real paren after the cursor
+        assert_eq!(matching_paren(&line, 2), Some(14));
+        assert_eq!(matching_paren(&chars("(ls"), 0), None);
+        assert_eq!(matching_paren(&chars("ls"), 0), None);
+    }
+}
@@ -3,8 +3,13 @@ use std::io;
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 
 use crate::{
+    autocomplete::{
+        binding::BindingCompleter, builtin::BuiltinCompleter, composite::CompositeCompleter,
+        executable::ExecutableCompleter, path::PathCompleter, slot::Slot,
+    },
     core::{EditorCore, Step},
     events::{EventSource, TerminalEventSource},
+    history::{self, History},
     renderer::{Renderer, TerminalRenderer},
 };
 
@@ -28,6 +33,7 @@ impl<E: EventSource, R: Renderer> Editor<E, R> {
 
         loop {
             self.renderer.render(self.core.view())?;
+            self.renderer.render_menu(self.core.menu())?;
 
             let ev = self.events.next_event()?;
             match self.core.handle(ev) {
@@ -51,14 +57,44 @@ pub type NashEditor = Editor<TerminalEventSource, TerminalRenderer<io::Stdout>>;
 
 impl Default for Editor<TerminalEventSource, TerminalRenderer<io::Stdout>> {
     fn default() -> Self {
+        Self::with_bindings(Vec::new)
+    }
+}
+
+impl Editor<TerminalEventSource, TerminalRenderer<io::Stdout>> {
+    /// An editor that also completes the names `bindings` lists as
+    /// commands, asking for them each time
+    pub fn with_bindings(bindings: impl Fn() -> Vec<String> + 'static) -> Self {
+        let commands = CompositeCompleter::new()
+            .with(BuiltinCompleter)
+            .with(BindingCompleter::new(bindings))
+            .with(ExecutableCompleter::new().scanning_path_in_background());
+
+        // Without a readable history file, history lasts the session
+        let history = history::default_location()
+            .and_then(|path| History::open(path).ok())
+            .unwrap_or_default();
+
         Editor {
-            core: EditorCore::new(),
+            core: EditorCore::new()
+                .with_history(history)
+                .with_completer(Slot::Command, commands)
+                .with_completer(Slot::Argument, PathCompleter::new())
+                .with_completer(Slot::File, PathCompleter::new()),
             events: TerminalEventSource,
             renderer: TerminalRenderer::new(io::stdout()),
         }
     }
 }
 
+impl<E: EventSource, R: Renderer> Editor<E, R> {
+    /// Edits lines the vi way
+    pub fn with_vi_mode(mut self) -> Self {
+        self.core = self.core.with_vi_mode();
+        self
+    }
+}
+
 pub struct RawModeGuard;
 
 impl RawModeGuard {
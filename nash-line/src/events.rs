@@ -21,12 +21,31 @@ impl EventSource for TerminalEventSource {
                     (KeyCode::Delete, _) => Ok(EditorEvent::Delete),
                     (KeyCode::Enter, _) => Ok(EditorEvent::Enter),
                     (KeyCode::Tab, _) => Ok(EditorEvent::Tab),
+                    (KeyCode::BackTab, _) => Ok(EditorEvent::BackTab),
+                    (KeyCode::Esc, _) => Ok(EditorEvent::Escape),
                     (KeyCode::Left, _) => Ok(EditorEvent::Left),
                     (KeyCode::Right, _) => Ok(EditorEvent::Right),
+                    (KeyCode::Up, _) => Ok(EditorEvent::Up),
+                    (KeyCode::Down, _) => Ok(EditorEvent::Down),
                     (KeyCode::Home, _) => Ok(EditorEvent::Home),
                     (KeyCode::End, _) => Ok(EditorEvent::End),
                     (KeyCode::Char('c'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlC),
+                    (KeyCode::Char('g'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlG),
                     (KeyCode::Char('j'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlJ),
+                    (KeyCode::Char('r'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlR),
+                    (KeyCode::Char('a'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlA),
+                    (KeyCode::Char('e'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlE),
+                    (KeyCode::Char('b'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlB),
+                    (KeyCode::Char('f'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlF),
+                    (KeyCode::Char('k'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlK),
+                    (KeyCode::Char('u'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlU),
+                    (KeyCode::Char('w'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlW),
+                    (KeyCode::Char('y'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlY),
+                    (KeyCode::Char('t'), KeyModifiers::CONTROL) => Ok(EditorEvent::CtrlT),
+                    (KeyCode::Char('b'), KeyModifiers::ALT) => Ok(EditorEvent::AltB),
+                    (KeyCode::Char('f'), KeyModifiers::ALT) => Ok(EditorEvent::AltF),
+                    (KeyCode::Char('d'), KeyModifiers::ALT) => Ok(EditorEvent::AltD),
+                    (KeyCode::Char('y'), KeyModifiers::ALT) => Ok(EditorEvent::AltY),
                     _ => continue
                 },
                 _ => continue
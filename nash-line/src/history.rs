@@ -0,0 +1,281 @@
+//! Lines the user has entered, kept across sessions
+//!
+//! Each shell reads the history file once when it starts, and
+//! appends every line it accepts under an exclusive lock, so shells
+//! running side by side never interleave half-written entries.
+//! Under the same lock the file is cut back to the newest
+//! [`MAX_ENTRIES`], so it doesn't grow without end.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// How many entries are kept, in memory and in the file
+pub const MAX_ENTRIES: usize = 10_000;
+
+#[derive(Debug)]
+pub struct History {
+    entries: Vec<String>,
+    /// Where entries are appended, if anywhere
+    file: Option<PathBuf>,
+    /// The most entries kept, oldest dropped first
+    limit: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            file: None,
+            limit: MAX_ENTRIES,
+        }
+    }
+}
+
+impl History {
+    /// A history kept only in memory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps at most `limit` entries instead of [`MAX_ENTRIES`]
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self.trim();
+        self
+    }
+
+    /// The history saved at `path`, which accepted lines are then
+    /// appended to. A file that doesn't exist yet is empty.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+
+        let entries = match File::open(&path) {
+            Ok(mut file) => {
+                file.lock_shared()?;
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)?;
+                contents.lines().map(decode).collect()
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e),
+        };
+
+        let mut history = Self {
+            entries,
+            file: Some(path),
+            ..Self::default()
+        };
+        history.trim();
+        Ok(history)
+    }
+
+    /// Records an accepted line. Blank lines and repeats of the line
+    /// before are skipped. The line is kept in memory even when
+    /// saving it fails.
+    pub fn push(&mut self, line: &str) -> io::Result<()> {
+        let line = line.trim_end_matches('\n');
+        if line.trim().is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return Ok(());
+        }
+
+        self.entries.push(line.to_string());
+        self.trim();
+
+        match &self.file {
+            Some(path) => append(path, line, self.limit),
+            None => Ok(()),
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Drops the oldest entries past the limit
+    fn trim(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.limit);
+        self.entries.drain(..excess);
+    }
+}
+
+/// Adds `line` to the file at `path`, then cuts it back to the
+/// newest `limit` lines while the lock is still held
+fn append(path: &Path, line: &str, limit: usize) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    file.lock()?;
+    file.write_all(format!("{}\n", encode(line)).as_bytes())?;
+
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_string(&mut contents)?;
+
+    let excess = contents.lines().count().saturating_sub(limit);
+    if excess == 0 {
+        return Ok(());
+    }
+
+    let kept: String = contents
+        .lines()
+        .skip(excess)
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    // Writes still go to the end, which is now the start
+    file.set_len(0)?;
+    file.write_all(kept.as_bytes())
+}
+
+/// An entry on a single line of the file - newlines in it become
+/// `\n`, so backslashes are doubled first
+fn encode(line: &str) -> String {
+    line.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn decode(line: &str) -> String {
+    let mut decoded = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                decoded.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                decoded.push('\\');
+                chars.next();
+            }
+            _ => decoded.push(c),
+        }
+    }
+
+    decoded
+}
+
+/// Where history is saved - under `$XDG_DATA_HOME`, or
+/// `~/.local/share`
+pub fn default_location() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?)
+            .join(".local")
+            .join("share"),
+    };
+
+    Some(base.join("nash").join("history"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::{TempDir, tempdir};
+
+    /// Where history is saved inside `dir`, in a directory that
+    /// doesn't exist yet
+    fn history_file(dir: &TempDir) -> PathBuf {
+        dir.path().join("nested").join("history")
+    }
+
+    #[test]
+    fn test_skips_blank_and_repeated_lines() {
+        let mut history = History::new();
+        history.push("(ls)").unwrap();
+        history.push("(ls)").unwrap();
+        history.push("  ").unwrap();
+        history.push("(pwd)\n").unwrap();
+        history.push("(ls)").unwrap();
+
+        assert_eq!(history.entries(), ["(ls)", "(pwd)", "(ls)"]);
+    }
+
+    #[test]
+    fn test_persists_across_sessions() {
+        let dir = tempdir().unwrap();
+        let path = history_file(&dir);
+
+        let mut first = History::open(&path).unwrap();
+        assert!(first.is_empty());
+        first.push("(echo one)").unwrap();
+        first.push("(echo 'two\nlines' \\n)").unwrap();
+
+        let second = History::open(&path).unwrap();
+        assert_eq!(second.entries(), first.entries());
+    }
+
+    #[test]
+    fn test_concurrent_shells_append() {
+        let dir = tempdir().unwrap();
+        let path = history_file(&dir);
+
+        let handles: Vec<_> = (0..4)
+            .map(|shell| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let mut history = History::open(&path).unwrap();
+                    for i in 0..25 {
+                        history.push(&format!("(echo {shell} {i})")).unwrap();
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let history = History::open(&path).unwrap();
+        assert_eq!(history.len(), 100);
+        assert!(history.entries().iter().all(|e| e.starts_with("(echo ")));
+    }
+
+    #[test]
+    fn test_oldest_entries_are_dropped() {
+        let dir = tempdir().unwrap();
+        let path = history_file(&dir);
+
+        let mut history = History::open(&path).unwrap().with_limit(3);
+        for i in 0..5 {
+            history.push(&format!("(echo {i})")).unwrap();
+        }
+        assert_eq!(history.entries(), ["(echo 2)", "(echo 3)", "(echo 4)"]);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        let reopened = History::open(&path).unwrap().with_limit(2);
+        assert_eq!(reopened.entries(), ["(echo 3)", "(echo 4)"]);
+    }
+
+    #[test]
+    fn test_encoding_round_trips() {
+        for line in [
+            "plain",
+            "a\nb",
+            "back\\slash",
+            "\\n literally",
+            "trailing\\",
+        ] {
+            assert_eq!(decode(&encode(line)), line);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Text the kill commands removed, for yanking back
+//!
+//! Like emacs, each kill goes on a ring. Yanking inserts the newest,
+//! and yank-pop swaps what was just yanked for the one before it,
+//! wrapping round to the newest again.
+
+use std::collections::VecDeque;
+
+/// How many kills are kept before the oldest is dropped
+const CAPACITY: usize = 32;
+
+#[derive(Debug, Default)]
+pub struct KillRing {
+    /// Oldest first
+    entries: VecDeque<String>,
+    /// How far back from the newest the last yank went
+    yanked: usize,
+}
+
+impl KillRing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Saves `text` as the newest kill. Killing nothing saves
+    /// nothing.
+    pub fn push(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(text.to_string());
+    }
+
+    /// Adds `text` to the end of the newest kill, for a kill that
+    /// carries on forward from the last
+    pub fn append(&mut self, text: &str) {
+        match self.entries.back_mut() {
+            Some(newest) => newest.push_str(text),
+            None => self.push(text),
+        }
+    }
+
+    /// Adds `text` to the start of the newest kill, for a kill that
+    /// carries on backward from the last
+    pub fn prepend(&mut self, text: &str) {
+        match self.entries.back_mut() {
+            Some(newest) => newest.insert_str(0, text),
+            None => self.push(text),
+        }
+    }
+
+    /// The newest kill
+    pub fn yank(&mut self) -> Option<&str> {
+        self.yanked = 0;
+        self.entries.back().map(String::as_str)
+    }
+
+    /// The kill before the one last yanked
+    pub fn rotate(&mut self) -> Option<&str> {
+        let len = self.entries.len();
+        if len == 0 {
+            return None;
+        }
+
+        self.yanked = (self.yanked + 1) % len;
+        self.entries.get(len - 1 - self.yanked).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yank_pop_walks_back_and_wraps() {
+        let mut ring = KillRing::new();
+        ring.push("one");
+        ring.push("");
+        ring.push("two");
+        ring.push("three");
+
+        assert_eq!(ring.yank(), Some("three"));
+        assert_eq!(ring.rotate(), Some("two"));
+        assert_eq!(ring.rotate(), Some("one"));
+        assert_eq!(ring.rotate(), Some("three"));
+        assert_eq!(ring.yank(), Some("three"));
+    }
+
+    #[test]
+    fn test_joined_kills() {
+        let mut ring = KillRing::new();
+        ring.append("ls");
+        ring.append(" -la");
+        ring.prepend("(");
+
+        assert_eq!(ring.yank(), Some("(ls -la"));
+        assert_eq!(ring.rotate(), Some("(ls -la"));
+    }
+
+    #[test]
+    fn test_oldest_kills_are_dropped() {
+        let mut ring = KillRing::new();
+        assert_eq!(ring.yank(), None);
+        assert_eq!(ring.rotate(), None);
+
+        for i in 0..=CAPACITY {
+            ring.push(&i.to_string());
+        }
+
+        assert_eq!(ring.entries.len(), CAPACITY);
+        assert_eq!(ring.entries.front().map(String::as_str), Some("1"));
+    }
+}
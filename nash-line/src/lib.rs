@@ -3,4 +3,6 @@ mod buffer;
 mod core;
 pub mod editor;
 mod events;
+pub mod history;
+mod killring;
 mod renderer;
@@ -2,35 +2,99 @@ use std::io::{self, Write};
 
 use crossterm::{
     QueueableCommand, cursor,
-    style::Print,
-    terminal::{Clear, ClearType},
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType},
 };
 
-use crate::core::EditorView;
+use crate::core::{EditorView, MenuView, Notice, SearchView, ViMode};
 
 pub trait Renderer {
     fn render(&mut self, editor_view: EditorView) -> io::Result<()>;
+    /// Draws `menu` as a grid under the line, or clears away the
+    /// last one drawn when there is none
+    fn render_menu(&mut self, menu: Option<MenuView>) -> io::Result<()>;
     fn commit(&mut self) -> io::Result<()>;
 }
 
 pub struct TerminalRenderer<W: Write> {
     out: W,
+    /// Where the cursor sits on the line, to come back to after
+    /// drawing below it
+    cursor_col: u16,
+    /// How many lines the menu under the line takes up
+    menu_rows: u16,
 }
 
 impl<W: Write> TerminalRenderer<W> {
     pub fn new(out: W) -> Self {
-        Self { out }
+        Self {
+            out,
+            cursor_col: 0,
+            menu_rows: 0,
+        }
+    }
+}
+
+impl<W: Write> TerminalRenderer<W> {
+    /// Draws the search prompt in place of the line, with the query
+    /// highlighted in the entry it matched and the cursor on it
+    fn render_search(&mut self, search: SearchView) -> io::Result<()> {
+        let prompt = search_prompt(&search);
+        let (entry, found) = search.matched.unwrap_or(("", None));
+        let found = found.unwrap_or(entry.len()..entry.len());
+        let (before, rest) = entry.split_at(found.start);
+        let (matched, after) = rest.split_at(found.len());
+
+        let column = prompt.chars().count() + before.chars().count();
+        self.cursor_col = column as u16;
+
+        self.out
+            .queue(cursor::MoveToColumn(0))?
+            .queue(Clear(ClearType::CurrentLine))?
+            .queue(Print(prompt))?
+            .queue(Print(before))?
+            .queue(SetAttribute(Attribute::Reverse))?
+            .queue(Print(matched))?
+            .queue(SetAttribute(Attribute::Reset))?
+            .queue(Print(after))?
+            .queue(cursor::MoveToColumn(self.cursor_col))?
+            .flush()?;
+
+        Ok(())
     }
 }
 
 impl<W: Write> Renderer for TerminalRenderer<W> {
     fn render(&mut self, editor_view: EditorView) -> io::Result<()> {
-        let final_cursor_position = (editor_view.cursor_col + 2) as u16;
+        if let Some(search) = editor_view.search {
+            return self.render_search(search);
+        }
+
+        let prompt = prompt(editor_view.mode);
+        let final_cursor_position = (prompt.chars().count() + editor_view.cursor_col) as u16;
+        self.cursor_col = final_cursor_position;
+
+        // Questions go below the line as it was, with the prompt
+        // drawn again underneath
+        match editor_view.notice {
+            Some(Notice::Bell) => {
+                self.out.queue(Print('\x07'))?;
+            }
+            Some(Notice::Confirm(count)) => {
+                self.out
+                    .queue(Print("\r\n"))?
+                    .queue(Print(format!(
+                        "Display all {count} possibilities? (y or n)"
+                    )))?
+                    .queue(Print("\r\n"))?;
+            }
+            None => {}
+        }
 
         self.out
             .queue(cursor::MoveToColumn(0))?
             .queue(Clear(ClearType::CurrentLine))?
-            .queue(Print("$ "))?
+            .queue(Print(prompt))?
             .queue(Print(editor_view.text))?
             .queue(cursor::MoveToColumn(final_cursor_position))?
             .flush()?;
@@ -38,7 +102,86 @@ impl<W: Write> Renderer for TerminalRenderer<W> {
         Ok(())
     }
 
+    fn render_menu(&mut self, menu: Option<MenuView>) -> io::Result<()> {
+        let Some(menu) = menu else {
+            if self.menu_rows > 0 {
+                self.out
+                    .queue(cursor::MoveToNextLine(1))?
+                    .queue(Clear(ClearType::FromCursorDown))?
+                    .queue(cursor::MoveUp(1))?
+                    .queue(cursor::MoveToColumn(self.cursor_col))?
+                    .flush()?;
+                self.menu_rows = 0;
+            }
+            return Ok(());
+        };
+
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        let (width, height) = (width as usize, height as usize);
+        let labels = labels(&menu);
+        let grid = layout(&labels, menu.selected, width, height.saturating_sub(1));
+
+        self.out
+            .queue(Print("\r\n"))?
+            .queue(Clear(ClearType::FromCursorDown))?;
+
+        for (i, row) in grid.rows.iter().enumerate() {
+            if i > 0 {
+                self.out.queue(Print("\r\n"))?;
+            }
+
+            for (j, &index) in row.iter().enumerate() {
+                let label: String = labels[index]
+                    .chars()
+                    .take(width.saturating_sub(1))
+                    .collect();
+                let (name, tag) =
+                    label.split_at(label.len().min(menu.candidates[index].text.len()));
+
+                if menu.selected == Some(index) {
+                    self.out
+                        .queue(SetAttribute(Attribute::Reverse))?
+                        .queue(Print(name))?
+                        .queue(SetAttribute(Attribute::Reset))?;
+                } else {
+                    self.out.queue(Print(name))?;
+                }
+
+                if !tag.is_empty() {
+                    self.out
+                        .queue(SetAttribute(Attribute::Dim))?
+                        .queue(Print(tag))?
+                        .queue(SetAttribute(Attribute::Reset))?;
+                }
+
+                if j + 1 < row.len() {
+                    let padding = grid.column_width - label.chars().count();
+                    self.out.queue(Print(" ".repeat(padding)))?;
+                }
+            }
+        }
+
+        let mut rows = grid.rows.len();
+        if grid.pages > 1 {
+            self.out.queue(Print(format!(
+                "\r\n-- page {} of {} --",
+                grid.page + 1,
+                grid.pages
+            )))?;
+            rows += 1;
+        }
+
+        self.menu_rows = rows as u16;
+        self.out
+            .queue(cursor::MoveUp(self.menu_rows))?
+            .queue(cursor::MoveToColumn(self.cursor_col))?
+            .flush()?;
+
+        Ok(())
+    }
+
     fn commit(&mut self) -> io::Result<()> {
+        self.render_menu(None)?;
         self.out
             .queue(Print('\n'))?
             .queue(cursor::MoveToColumn(0))?
@@ -47,3 +190,185 @@ impl<W: Write> Renderer for TerminalRenderer<W> {
         Ok(())
     }
 }
+
+/// What comes before the line, marked with the mode when editing
+/// the vi way
+fn prompt(mode: Option<ViMode>) -> &'static str {
+    match mode {
+        None => "$ ",
+        Some(ViMode::Insert) => "[I] $ ",
+        Some(ViMode::Normal) => "[N] $ ",
+    }
+}
+
+/// What comes before the entry found while searching, bash style
+fn search_prompt(search: &SearchView) -> String {
+    let failed = if search.failed { "failed " } else { "" };
+    format!("({failed}reverse-i-search)`{}': ", search.query)
+}
+
+/// What the menu shows for each candidate - its text, followed by
+/// its kind when the candidates aren't all of one kind, and any
+/// detail it has
+fn labels(menu: &MenuView) -> Vec<String> {
+    let mixed = menu
+        .candidates
+        .windows(2)
+        .any(|pair| pair[0].kind != pair[1].kind);
+
+    menu.candidates
+        .iter()
+        .map(|c| {
+            let mut label = c.text.clone();
+            if mixed {
+                label = format!("{label} {}", c.kind.tag());
+            }
+            if let Some(detail) = &c.detail {
+                label = format!("{label} {detail}");
+            }
+            label
+        })
+        .collect()
+}
+
+/// Candidates laid out to fit the terminal. `rows` index into the
+/// candidates and only cover the page holding the selection.
+#[derive(Debug, PartialEq)]
+struct Grid {
+    rows: Vec<Vec<usize>>,
+    column_width: usize,
+    page: usize,
+    pages: usize,
+}
+
+/// Lays `candidates` out row by row in columns that fit `width`,
+/// paging when there are more rows than `height`
+fn layout(
+    candidates: &[impl AsRef<str>],
+    selected: Option<usize>,
+    width: usize,
+    height: usize,
+) -> Grid {
+    let longest = candidates.iter().map(|c| c.as_ref().chars().count()).max();
+    let column_width = longest.unwrap_or(0) + 2;
+    let columns = (width / column_width).max(1);
+    let total_rows = candidates.len().div_ceil(columns);
+
+    // Paging takes a row of its own to say which page is showing
+    let rows_per_page = if total_rows > height {
+        height.saturating_sub(1).max(1)
+    } else {
+        total_rows.max(1)
+    };
+    let per_page = rows_per_page * columns;
+    let pages = candidates.len().div_ceil(per_page).max(1);
+    let page = selected.unwrap_or(0) / per_page;
+
+    let start = page * per_page;
+    let end = (start + per_page).min(candidates.len());
+    let indices: Vec<usize> = (start..end).collect();
+
+    Grid {
+        rows: indices.chunks(columns).map(<[usize]>::to_vec).collect(),
+        column_width,
+        page,
+        pages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autocomplete::{Completion, Kind};
+
+    fn completion(text: &str, kind: Kind) -> Completion {
+        Completion {
+            text: text.to_string(),
+            span: 0..0,
+            kind,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn test_search_prompt() {
+        let mut search = SearchView {
+            query: "gi",
+            matched: Some(("git status", Some(0..2))),
+            failed: false,
+        };
+        assert_eq!(search_prompt(&search), "(reverse-i-search)`gi': ");
+
+        search.failed = true;
+        assert_eq!(search_prompt(&search), "(failed reverse-i-search)`gi': ");
+    }
+
+    #[test]
+    fn test_prompt_shows_vi_mode() {
+        assert_eq!(prompt(None), "$ ");
+        assert_eq!(prompt(Some(ViMode::Insert)), "[I] $ ");
+        assert_eq!(prompt(Some(ViMode::Normal)), "[N] $ ");
+    }
+
+    #[test]
+    fn test_labels_tag_mixed_kinds() {
+        let same = [
+            completion("ls", Kind::Executable),
+            completion("lsblk", Kind::Executable),
+        ];
+        let menu = MenuView {
+            candidates: &same,
+            selected: None,
+        };
+        assert_eq!(labels(&menu), vec!["ls", "lsblk"]);
+
+        let mixed = [
+            completion("pipe", Kind::Builtin),
+            completion("pip", Kind::Executable),
+        ];
+        let menu = MenuView {
+            candidates: &mixed,
+            selected: None,
+        };
+        assert_eq!(labels(&menu), vec!["pipe builtin", "pip executable"]);
+
+        let detailed = [completion("ls", Kind::Executable).with_detail("/bin/ls")];
+        let menu = MenuView {
+            candidates: &detailed,
+            selected: None,
+        };
+        assert_eq!(labels(&menu), vec!["ls /bin/ls"]);
+    }
+
+    fn names(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("cmd{i:02}")).collect()
+    }
+
+    #[test]
+    fn test_layout_fills_rows_to_width() {
+        // "cmd00" plus two spaces is 7 wide, so 3 fit in 21
+        let grid = layout(&names(7), None, 21, 24);
+        assert_eq!(grid.column_width, 7);
+        assert_eq!(grid.rows, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+        assert_eq!(grid.pages, 1);
+    }
+
+    #[test]
+    fn test_layout_narrow_terminal_is_one_column() {
+        let grid = layout(&names(2), None, 4, 24);
+        assert_eq!(grid.rows, vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn test_layout_pages_to_the_selection() {
+        // 2 columns, 3 rows a page once one is kept for the page count
+        let candidates = names(20);
+        let first = layout(&candidates, None, 14, 4);
+        assert_eq!(first.rows, vec![vec![0, 1], vec![2, 3], vec![4, 5]]);
+        assert_eq!((first.page, first.pages), (0, 4));
+
+        let later = layout(&candidates, Some(13), 14, 4);
+        assert_eq!(later.page, 2);
+        assert_eq!(later.rows[0], vec![12, 13]);
+    }
+}
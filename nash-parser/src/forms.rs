@@ -0,0 +1,90 @@
+//! The special forms, and the operators that invoke them.
+//!
+//! Lists whose operator is one of these are evaluated by the shell
+//! itself rather than run as a program. Only a bare symbol invokes
+//! a form - `('pipe' ...)` runs a program called `pipe`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Form {
+    Def,
+    Pipe,
+    And,
+    Or,
+    Not,
+    If,
+    In,
+    Out,
+    Append,
+}
+
+impl Form {
+    pub const ALL: [Form; 9] = [
+        Form::Def,
+        Form::Pipe,
+        Form::And,
+        Form::Or,
+        Form::Not,
+        Form::If,
+        Form::In,
+        Form::Out,
+        Form::Append,
+    ];
+
+    /// The form `operator` invokes, if any
+    pub fn from_operator(operator: &str) -> Option<Form> {
+        Form::ALL
+            .into_iter()
+            .find(|form| form.operators().contains(&operator))
+    }
+
+    /// Every operator that invokes the form, its name first
+    pub fn operators(self) -> &'static [&'static str] {
+        match self {
+            Form::Def => &["def"],
+            Form::Pipe => &["pipe", "|"],
+            Form::And => &["and"],
+            Form::Or => &["or"],
+            Form::Not => &["not"],
+            Form::If => &["if"],
+            Form::In => &["in", "<"],
+            Form::Out => &["out", ">"],
+            Form::Append => &["append", ">>"],
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        self.operators()[0]
+    }
+
+    /// Whether the form redirects a command to or from a file,
+    /// which is written last
+    pub fn is_redirect(self) -> bool {
+        matches!(self, Form::In | Form::Out | Form::Append)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operators_find_their_form() {
+        assert_eq!(Form::from_operator("pipe"), Some(Form::Pipe));
+        assert_eq!(Form::from_operator("|"), Some(Form::Pipe));
+        assert_eq!(Form::from_operator(">>"), Some(Form::Append));
+        assert_eq!(Form::from_operator("ls"), None);
+    }
+
+    #[test]
+    fn test_every_operator_is_unique() {
+        let mut operators: Vec<&str> = Form::ALL
+            .iter()
+            .flat_map(|f| f.operators())
+            .copied()
+            .collect();
+        let count = operators.len();
+        operators.sort();
+        operators.dedup();
+        assert_eq!(operators.len(), count);
+    }
+}
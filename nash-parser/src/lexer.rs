@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::span::Span;
+
 #[derive(Debug)]
 enum LexerState {
     Normal,
@@ -19,61 +21,147 @@ pub enum Token {
     Closed,
 }
 
+#[derive(PartialEq, Debug, Clone)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
 #[derive(Error, Debug)]
 pub enum LexError {
+    /// `span` runs from the opening quote to the end of input
     #[error("literal not terminated")]
-    UnterminatedLiteral,
+    UnterminatedLiteral { span: Span },
 
+    /// `span` covers the trailing backslash
     #[error("trailing escape")]
-    OpenEscape,
+    OpenEscape { span: Span },
+}
+
+/// Where a [`scan`] stopped
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ScanEnd {
+    /// Between tokens, or just after a symbol
+    Normal,
+    /// Inside a `;` comment
+    Comment,
+    /// Inside a literal opened with `quote`
+    Literal { quote: char },
+    /// Just after a backslash outside any literal
+    Escape,
+}
+
+/// Everything the lexer made of `input`, even when it is incomplete
+#[derive(Debug)]
+pub struct Scan {
+    /// A literal or symbol cut off by the end of input is still
+    /// pushed, holding what was read of it
+    pub tokens: Vec<SpannedToken>,
+    pub end: ScanEnd,
+    /// What [`lex_spanned`] would have failed with
+    pub error: Option<LexError>,
+}
+
+impl LexError {
+    pub fn span(&self) -> Span {
+        match self {
+            LexError::UnterminatedLiteral { span } | LexError::OpenEscape { span } => *span,
+        }
+    }
 }
 
 pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
+    let tokens = lex_spanned(input)?;
+    Ok(tokens.into_iter().map(|t| t.token).collect())
+}
+
+/// Like [`lex`], but keeps where in `input` each token came from
+pub fn lex_spanned(input: &str) -> Result<Vec<SpannedToken>, LexError> {
+    let scan = scan(input);
+    match scan.error {
+        Some(e) => Err(e),
+        None => Ok(scan.tokens),
+    }
+}
+
+/// Lexes as much of `input` as possible, never failing. Used where
+/// input is still being typed.
+pub fn scan(input: &str) -> Scan {
     let mut current_lex_state = LexerState::Normal;
     let mut tokens = Vec::new();
     let mut current_token = String::new();
 
-    // Push a token, wrapped in specified Enum
+    // Where the token being built started, and where we are now
+    let mut token_start: Option<Span> = None;
+    let mut last_char: Option<Span> = None;
+    let mut line = 1;
+    let mut column = 1;
+
+    // Push a token, wrapped in specified Enum, ending at byte `$end`
     macro_rules! push_token {
-        ($constructor:expr) => {
-            if !current_token.is_empty() {
-                tokens.push($constructor(current_token.clone()));
-                current_token.clear();
+        ($constructor:expr, $end:expr) => {
+            if let Some(start) = token_start.take() {
+                if !current_token.is_empty() {
+                    tokens.push(SpannedToken {
+                        token: $constructor(current_token.clone()),
+                        span: Span { end: $end, ..start },
+                    });
+                    current_token.clear();
+                }
             }
         };
     }
 
-    for c in input.chars() {
+    for (i, c) in input.char_indices() {
+        let here = Span::new(i, i + c.len_utf8(), line, column);
+        last_char = Some(here);
+
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+
         match current_lex_state {
             LexerState::Normal => {
                 if c.is_whitespace() {
-                    push_token!(Token::Symbol);
+                    push_token!(Token::Symbol, i);
                 } else if c == '"' {
-                    push_token!(Token::Symbol);
+                    push_token!(Token::Symbol, i);
+                    token_start = Some(here);
                     current_lex_state = LexerState::DoubleLiteral;
                 } else if c == '\'' {
-                    push_token!(Token::Symbol);
+                    push_token!(Token::Symbol, i);
+                    token_start = Some(here);
                     current_lex_state = LexerState::SingleLiteral;
                 } else if c == '(' {
-                    push_token!(Token::Symbol);
-                    tokens.push(Token::Open);
+                    push_token!(Token::Symbol, i);
+                    tokens.push(SpannedToken {
+                        token: Token::Open,
+                        span: here,
+                    });
                 } else if c == ')' {
-                    push_token!(Token::Symbol);
-                    tokens.push(Token::Closed);
+                    push_token!(Token::Symbol, i);
+                    tokens.push(SpannedToken {
+                        token: Token::Closed,
+                        span: here,
+                    });
                 } else if c == '\\' {
+                    token_start.get_or_insert(here);
                     current_lex_state = LexerState::Escaped;
                 } else if c == ';' {
+                    push_token!(Token::Symbol, i);
                     current_lex_state = LexerState::Comment;
                 } else {
+                    token_start.get_or_insert(here);
                     current_token.push(c);
                 }
             }
             LexerState::Escaped => {
                 const ESCAPEDCHARS: &[char] = &['(', ')', '"', '\''];
 
-                if ESCAPEDCHARS.contains(&c) {
-                    current_token.push(c);
-                } else if c.is_whitespace() {
+                if ESCAPEDCHARS.contains(&c) || c.is_whitespace() {
                     current_token.push(c);
                 } else if c == '\\' {
                     current_token.push('\\');
@@ -87,7 +175,8 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
             }
             LexerState::DoubleLiteral => {
                 if c == '"' {
-                    push_token!(Token::Literal);
+                    push_token!(Token::Literal, i + 1);
+                    token_start = None;
                     current_lex_state = LexerState::Normal;
                 } else if c == '\\' {
                     current_lex_state = LexerState::DoubleLiteralEscaped;
@@ -97,7 +186,8 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
             }
             LexerState::SingleLiteral => {
                 if c == '\'' {
-                    push_token!(Token::Literal);
+                    push_token!(Token::Literal, i + 1);
+                    token_start = None;
                     current_lex_state = LexerState::Normal;
                 } else if c == '\\' {
                     current_lex_state = LexerState::SingleLiteralEscaped;
@@ -139,21 +229,64 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
         }
     }
 
-    match current_lex_state {
-        LexerState::Normal | LexerState::Comment => {
-            push_token!(Token::Symbol)
+    let end = input.len();
+
+    // Push the literal being read, however much of it there is
+    macro_rules! push_unterminated {
+        () => {{
+            let start = token_start.take().unwrap_or_default();
+            tokens.push(SpannedToken {
+                token: Token::Literal(current_token.clone()),
+                span: Span { end, ..start },
+            });
+            start
+        }};
+    }
+
+    let (end_state, error) = match current_lex_state {
+        LexerState::Normal => {
+            push_token!(Token::Symbol, end);
+            (ScanEnd::Normal, None)
         }
+        LexerState::Comment => (ScanEnd::Comment, None),
         LexerState::DoubleLiteral | LexerState::SingleLiteral => {
-            return Err(LexError::UnterminatedLiteral);
+            let quote = if matches!(current_lex_state, LexerState::DoubleLiteral) {
+                '"'
+            } else {
+                '\''
+            };
+            let start = push_unterminated!();
+            let span = Span { end, ..start };
+            (
+                ScanEnd::Literal { quote },
+                Some(LexError::UnterminatedLiteral { span }),
+            )
         }
-        LexerState::Escaped
-        | LexerState::DoubleLiteralEscaped
-        | LexerState::SingleLiteralEscaped => {
-            return Err(LexError::OpenEscape);
+        LexerState::DoubleLiteralEscaped | LexerState::SingleLiteralEscaped => {
+            let quote = if matches!(current_lex_state, LexerState::DoubleLiteralEscaped) {
+                '"'
+            } else {
+                '\''
+            };
+            push_unterminated!();
+            let span = last_char.unwrap_or_default();
+            (
+                ScanEnd::Literal { quote },
+                Some(LexError::OpenEscape { span }),
+            )
         }
-    }
+        LexerState::Escaped => {
+            push_token!(Token::Symbol, end);
+            let span = last_char.unwrap_or_default();
+            (ScanEnd::Escape, Some(LexError::OpenEscape { span }))
+        }
+    };
 
-    Ok(tokens)
+    Scan {
+        tokens,
+        end: end_state,
+        error,
+    }
 }
 
 #[cfg(test)]
@@ -263,4 +396,103 @@ mod tests {
             ]
         );
     }
+
+    fn spans(input: &str) -> Vec<(usize, usize, usize, usize)> {
+        lex_spanned(input)
+            .expect("expected lex_spanned() to return Ok(...)")
+            .into_iter()
+            .map(|t| (t.span.start, t.span.end, t.span.line, t.span.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_spans_of_list() {
+        assert_eq!(
+            spans("(ls -la)"),
+            vec![(0, 1, 1, 1), (1, 3, 1, 2), (4, 7, 1, 5), (7, 8, 1, 8)]
+        );
+    }
+
+    #[test]
+    fn test_literal_span_includes_quotes() {
+        assert_eq!(
+            spans("(echo 'a b')"),
+            vec![(0, 1, 1, 1), (1, 5, 1, 2), (6, 11, 1, 7), (11, 12, 1, 12)]
+        );
+    }
+
+    #[test]
+    fn test_escaped_symbol_span_starts_at_backslash() {
+        assert_eq!(spans("a \\(b"), vec![(0, 1, 1, 1), (2, 5, 1, 3)]);
+    }
+
+    #[test]
+    fn test_spans_track_lines() {
+        assert_eq!(
+            spans("; comment\n(ls)"),
+            vec![(10, 11, 2, 1), (11, 13, 2, 2), (13, 14, 2, 4)]
+        );
+    }
+
+    #[test]
+    fn test_columns_count_chars() {
+        assert_eq!(spans("é x"), vec![(0, 2, 1, 1), (3, 4, 1, 3)]);
+    }
+
+    #[test]
+    fn test_comment_ends_symbol() {
+        assert_eq!(
+            lex_ok("ls; comment\nfoo"),
+            vec![
+                Token::Symbol("ls".to_string()),
+                Token::Symbol("foo".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_literal_span() {
+        match lex("(echo 'oops)") {
+            Err(LexError::UnterminatedLiteral { span }) => {
+                assert_eq!((span.start, span.end, span.column), (6, 12, 7))
+            }
+            _ => panic!("expected UnterminatedLiteral"),
+        }
+    }
+
+    #[test]
+    fn test_open_escape_span() {
+        match lex("ab\\") {
+            Err(LexError::OpenEscape { span }) => assert_eq!((span.start, span.end), (2, 3)),
+            _ => panic!("expected OpenEscape"),
+        }
+    }
+
+    #[test]
+    fn test_scan_keeps_unterminated_literal() {
+        let scan = scan("(echo 'hel");
+        assert_eq!(scan.end, ScanEnd::Literal { quote: '\'' });
+        assert!(matches!(
+            scan.error,
+            Some(LexError::UnterminatedLiteral { .. })
+        ));
+
+        let last = scan.tokens.last().unwrap();
+        assert_eq!(last.token, Token::Literal("hel".to_string()));
+        assert_eq!((last.span.start, last.span.end), (6, 10));
+    }
+
+    #[test]
+    fn test_scan_ends() {
+        assert_eq!(scan("(ls) ").end, ScanEnd::Normal);
+        assert_eq!(scan("(ls) ; note").end, ScanEnd::Comment);
+        assert_eq!(scan("\"the \\").end, ScanEnd::Literal { quote: '"' });
+
+        let escaped = scan("(cat my\\");
+        assert_eq!(escaped.end, ScanEnd::Escape);
+        assert_eq!(
+            escaped.tokens.last().unwrap().token,
+            Token::Symbol("my".to_string())
+        );
+    }
 }
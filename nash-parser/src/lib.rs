@@ -1,2 +1,5 @@
+pub mod forms;
 pub mod lexer;
 pub mod parser;
+pub mod partial;
+pub mod span;
@@ -1,14 +1,28 @@
 use thiserror::Error;
 
-use crate::lexer;
-
+use crate::{
+    lexer::{self, SpannedToken},
+    span::Span,
+};
+
+/// A parsed expression. A list's span runs from its `(` through
+/// its `)`, an atom's covers the token it came from - including
+/// any quotes.
 #[derive(Debug)]
 pub enum Expression {
-    List(Vec<Expression>),
-    Atom(Atom),
+    List(Vec<Expression>, Span),
+    Atom(Atom, Span),
 }
 
-#[derive(Debug)]
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::List(_, span) | Expression::Atom(_, span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub enum Atom {
     Literal(String),
     Symbol(String),
@@ -20,22 +34,38 @@ pub enum ParseError {
     LexError(#[from] crate::lexer::LexError),
 
     #[error("expected atom, got: {got:?}")]
-    ExpectedAtom { got: crate::lexer::Token },
+    ExpectedAtom {
+        got: crate::lexer::Token,
+        span: Span,
+    },
 
     #[error("expected expression")]
-    ExpectedExpression,
+    ExpectedExpression { span: Span },
 
+    /// `span` covers the `(` that was never closed
     #[error("expected ')'")]
-    ExpectedClosed,
+    ExpectedClosed { span: Span },
 
     #[error("unexpected ')'")]
-    UnexpectedClosed,
+    UnexpectedClosed { span: Span },
+}
+
+impl ParseError {
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::LexError(e) => e.span(),
+            ParseError::ExpectedAtom { span, .. }
+            | ParseError::ExpectedExpression { span }
+            | ParseError::ExpectedClosed { span }
+            | ParseError::UnexpectedClosed { span } => *span,
+        }
+    }
 }
 
 pub fn parse(input: &str) -> Result<Vec<Expression>, ParseError> {
-    let tokens = lexer::lex(input)?;
+    let tokens = lexer::lex_spanned(input)?;
 
-    let mut rest: &[lexer::Token] = &tokens;
+    let mut rest: &[SpannedToken] = &tokens;
     let mut expressions = Vec::new();
 
     while !rest.is_empty() {
@@ -45,48 +75,127 @@ pub fn parse(input: &str) -> Result<Vec<Expression>, ParseError> {
     Ok(expressions)
 }
 
-fn peek(input: &[lexer::Token]) -> Option<&lexer::Token> {
+fn peek(input: &[SpannedToken]) -> Option<&SpannedToken> {
     input.first()
 }
 
-fn next(input: &mut &[lexer::Token]) -> Option<lexer::Token> {
+fn next(input: &mut &[SpannedToken]) -> Option<SpannedToken> {
     let (first, rest) = input.split_first()?;
     *input = rest;
     Some(first.clone())
 }
 
-fn parse_expr(input: &mut &[lexer::Token]) -> Result<Expression, ParseError> {
-    let token = next(input).ok_or(ParseError::ExpectedExpression)?;
-    match token {
-        lexer::Token::Open => Ok(Expression::List(parse_list(input)?)),
+fn parse_expr(input: &mut &[SpannedToken]) -> Result<Expression, ParseError> {
+    let token = next(input).ok_or(ParseError::ExpectedExpression {
+        span: Span::default(),
+    })?;
+    match token.token {
+        lexer::Token::Open => {
+            let (expressions, close) = parse_list(input, token.span)?;
+            Ok(Expression::List(expressions, token.span.to(close)))
+        }
         lexer::Token::Symbol(_) | lexer::Token::Literal(_) => {
-            Ok(Expression::Atom(parse_atom(token)?))
+            let span = token.span;
+            Ok(Expression::Atom(parse_atom(token)?, span))
         }
-        lexer::Token::Closed => Err(ParseError::UnexpectedClosed),
+        lexer::Token::Closed => Err(ParseError::UnexpectedClosed { span: token.span }),
     }
 }
 
-fn parse_list(input: &mut &[lexer::Token]) -> Result<Vec<Expression>, ParseError> {
+/// Parses the rest of a list opened at `open`, returning its
+/// expressions and the span of the closing `)`
+fn parse_list(
+    input: &mut &[SpannedToken],
+    open: Span,
+) -> Result<(Vec<Expression>, Span), ParseError> {
     let mut expressions = vec![];
 
     loop {
         match peek(input) {
-            None => return Err(ParseError::ExpectedClosed),
-            Some(lexer::Token::Closed) => {
+            None => return Err(ParseError::ExpectedClosed { span: open }),
+            Some(SpannedToken {
+                token: lexer::Token::Closed,
+                span,
+            }) => {
+                let close = *span;
                 _ = next(input); // consume ')'
-                break;
+                return Ok((expressions, close));
             }
             Some(_) => expressions.push(parse_expr(input)?),
         }
     }
-
-    Ok(expressions)
 }
 
-fn parse_atom(token: lexer::Token) -> Result<Atom, ParseError> {
-    match token {
+fn parse_atom(token: SpannedToken) -> Result<Atom, ParseError> {
+    match token.token {
         lexer::Token::Literal(s) => Ok(Atom::Literal(s)),
         lexer::Token::Symbol(s) => Ok(Atom::Symbol(s)),
-        got => Err(ParseError::ExpectedAtom { got }),
+        got => Err(ParseError::ExpectedAtom {
+            got,
+            span: token.span,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_span_covers_parens() {
+        let exprs = parse("  (ls -la)").unwrap();
+        let span = exprs[0].span();
+        assert_eq!(
+            (span.start, span.end, span.line, span.column),
+            (2, 10, 1, 3)
+        );
+    }
+
+    #[test]
+    fn test_atom_spans() {
+        let exprs = parse("(echo\n  'hi')").unwrap();
+        match &exprs[0] {
+            Expression::List(items, _) => {
+                let span = items[1].span();
+                assert_eq!(
+                    (span.start, span.end, span.line, span.column),
+                    (8, 12, 2, 3)
+                );
+            }
+            _ => panic!("expected list"),
+        }
+    }
+
+    #[test]
+    fn test_nested_list_spans() {
+        let exprs = parse("(a (b c))").unwrap();
+        match &exprs[0] {
+            Expression::List(items, span) => {
+                assert_eq!((span.start, span.end), (0, 9));
+                assert_eq!((items[1].span().start, items[1].span().end), (3, 8));
+            }
+            _ => panic!("expected list"),
+        }
+    }
+
+    #[test]
+    fn test_unclosed_list_points_at_open_paren() {
+        let err = parse("(ls)\n(a (b c)").unwrap_err();
+        assert!(matches!(err, ParseError::ExpectedClosed { .. }));
+        let span = err.span();
+        assert_eq!((span.start, span.line, span.column), (5, 2, 1));
+    }
+
+    #[test]
+    fn test_unexpected_close_span() {
+        let err = parse("(ls))").unwrap_err();
+        assert!(matches!(err, ParseError::UnexpectedClosed { .. }));
+        assert_eq!(err.span().start, 4);
+    }
+
+    #[test]
+    fn test_lex_error_span_passes_through() {
+        let err = parse("(echo \"oops)").unwrap_err();
+        assert_eq!(err.span().start, 6);
     }
 }
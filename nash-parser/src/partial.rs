@@ -0,0 +1,457 @@
+//! Parsing for input that is still being typed.
+//!
+//! [`parse_partial`] never fails - it returns whatever tree it can,
+//! with error nodes where the input is broken or unfinished.
+//! [`context_at`] uses it to say what a cursor is sitting in.
+
+use crate::{
+    lexer::{self, ScanEnd, Token},
+    parser::Atom,
+    span::Span,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum PartialExpression {
+    /// Spans from the `(` through the `)`, or to the end of input
+    /// when the list is unclosed
+    List(Vec<PartialExpression>, Span),
+    Atom(Atom, Span),
+    Error(SyntaxError, Span),
+}
+
+impl PartialExpression {
+    pub fn span(&self) -> Span {
+        match self {
+            PartialExpression::List(_, span)
+            | PartialExpression::Atom(_, span)
+            | PartialExpression::Error(_, span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SyntaxError {
+    /// Ends a list the input never closed. The span is empty, at
+    /// the end of input.
+    MissingClose,
+    /// A `)` with no list to close
+    UnexpectedClose,
+    /// A literal cut off by the end of input, holding what was read
+    UnterminatedLiteral(String),
+    /// A trailing backslash outside any literal
+    OpenEscape,
+}
+
+#[derive(Debug)]
+pub struct PartialParse {
+    pub expressions: Vec<PartialExpression>,
+    /// Where the input stopped - in a comment, a literal and so on
+    pub end: ScanEnd,
+}
+
+impl PartialParse {
+    /// Whether the input parsed without any error nodes
+    pub fn is_complete(&self) -> bool {
+        fn complete(expressions: &[PartialExpression]) -> bool {
+            expressions.iter().all(|e| match e {
+                PartialExpression::List(items, _) => complete(items),
+                PartialExpression::Atom(..) => true,
+                PartialExpression::Error(..) => false,
+            })
+        }
+
+        complete(&self.expressions)
+    }
+}
+
+/// Where a cursor sits, syntactically
+#[derive(Debug, PartialEq)]
+pub struct CursorContext {
+    pub position: Position,
+    /// The word the cursor is at the end of. When the cursor is
+    /// between words this is empty, with an empty span at the cursor.
+    pub word: Word,
+}
+
+impl CursorContext {
+    /// Whether the cursor is inside a literal that is still open
+    pub fn in_literal(&self) -> bool {
+        matches!(
+            self.word.kind,
+            WordKind::Literal {
+                terminated: false,
+                ..
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Position {
+    /// Outside any list
+    TopLevel,
+    /// Inside a `;` comment
+    Comment,
+    /// The first element of a list
+    Command,
+    /// Element `index` of a list, counting the command as 0.
+    /// `operator` is the command when it is a plain symbol.
+    Argument {
+        operator: Option<String>,
+        index: usize,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Word {
+    /// The word as the lexer reads it - quotes and escapes removed
+    pub text: String,
+    /// Where the word was written, quotes included
+    pub span: Span,
+    pub kind: WordKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WordKind {
+    Symbol,
+    Literal { quote: char, terminated: bool },
+}
+
+pub fn parse_partial(input: &str) -> PartialParse {
+    let scan = lexer::scan(input);
+    let count = scan.tokens.len();
+
+    // The lists still open, innermost last, under the top level
+    let mut open: Vec<(Vec<PartialExpression>, Span)> = vec![];
+    let mut current = vec![];
+
+    for (i, spanned) in scan.tokens.into_iter().enumerate() {
+        let span = spanned.span;
+        let cut_off = i + 1 == count && matches!(scan.end, ScanEnd::Literal { .. });
+
+        match spanned.token {
+            Token::Open => open.push((std::mem::take(&mut current), span)),
+            Token::Closed => match open.pop() {
+                Some((parent, start)) => {
+                    let items = std::mem::replace(&mut current, parent);
+                    current.push(PartialExpression::List(items, start.to(span)));
+                }
+                None => current.push(PartialExpression::Error(SyntaxError::UnexpectedClose, span)),
+            },
+            Token::Literal(s) if cut_off => {
+                current.push(PartialExpression::Error(
+                    SyntaxError::UnterminatedLiteral(s),
+                    span,
+                ));
+            }
+            Token::Literal(s) => current.push(PartialExpression::Atom(Atom::Literal(s), span)),
+            Token::Symbol(s) => current.push(PartialExpression::Atom(Atom::Symbol(s), span)),
+        }
+    }
+
+    let end = span_at(input, input.len());
+
+    if scan.end == ScanEnd::Escape {
+        let span = Span {
+            start: input.len() - 1,
+            column: end.column - 1,
+            ..end
+        };
+        current.push(PartialExpression::Error(SyntaxError::OpenEscape, span));
+    }
+
+    while let Some((parent, start)) = open.pop() {
+        let mut items = std::mem::replace(&mut current, parent);
+        items.push(PartialExpression::Error(SyntaxError::MissingClose, end));
+        current.push(PartialExpression::List(items, start.to(end)));
+    }
+
+    PartialParse {
+        expressions: current,
+        end: scan.end,
+    }
+}
+
+/// The syntactic context of a cursor at byte `offset` in `input`.
+/// Only what comes before the cursor is considered.
+pub fn context_at(input: &str, offset: usize) -> CursorContext {
+    let mut offset = offset.min(input.len());
+    while !input.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &input[..offset];
+    let parse = parse_partial(before);
+    let empty = Word {
+        text: String::new(),
+        span: span_at(before, offset),
+        kind: WordKind::Symbol,
+    };
+
+    if parse.end == ScanEnd::Comment {
+        return CursorContext {
+            position: Position::Comment,
+            word: empty,
+        };
+    }
+
+    // Follow the unclosed lists down to the innermost one
+    let mut items: &[PartialExpression] = &parse.expressions;
+    let mut top_level = true;
+    while let Some(PartialExpression::List(inner, _)) = items.last()
+        && let Some(PartialExpression::Error(SyntaxError::MissingClose, _)) = inner.last()
+    {
+        items = &inner[..inner.len() - 1];
+        top_level = false;
+    }
+
+    // A symbol cut off by a trailing backslash already spans it
+    let escape = match items.last() {
+        Some(PartialExpression::Error(SyntaxError::OpenEscape, span)) => {
+            items = &items[..items.len() - 1];
+            Some(*span)
+        }
+        _ => None,
+    };
+
+    let word = match items.last() {
+        Some(last) if last.span().end == offset => word_from(before, last, offset),
+        _ => None,
+    };
+
+    let index = match word {
+        Some(_) => items.len() - 1,
+        None => items.len(),
+    };
+    let word = word.unwrap_or(match escape {
+        Some(span) => Word { span, ..empty },
+        None => empty,
+    });
+
+    let position = if top_level {
+        Position::TopLevel
+    } else if index == 0 {
+        Position::Command
+    } else {
+        let operator = match items.first() {
+            Some(PartialExpression::Atom(Atom::Symbol(s), _)) => Some(s.clone()),
+            _ => None,
+        };
+        Position::Argument { operator, index }
+    };
+
+    CursorContext { position, word }
+}
+
+/// `expression` as a word ending at `offset`, if it is one
+fn word_from(input: &str, expression: &PartialExpression, offset: usize) -> Option<Word> {
+    let quote = |span: Span| input[span.start..].chars().next().unwrap_or('"');
+
+    let (text, span, kind) = match expression {
+        PartialExpression::Atom(Atom::Symbol(s), span) => (s, *span, WordKind::Symbol),
+        PartialExpression::Atom(Atom::Literal(s), span) => (
+            s,
+            *span,
+            WordKind::Literal {
+                quote: quote(*span),
+                terminated: true,
+            },
+        ),
+        PartialExpression::Error(SyntaxError::UnterminatedLiteral(s), span) => (
+            s,
+            *span,
+            WordKind::Literal {
+                quote: quote(*span),
+                terminated: false,
+            },
+        ),
+        _ => return None,
+    };
+
+    Some(Word {
+        text: text.clone(),
+        span: Span {
+            end: offset,
+            ..span
+        },
+        kind,
+    })
+}
+
+/// An empty span at byte `offset` of `input`
+fn span_at(input: &str, offset: usize) -> Span {
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+
+    Span::new(offset, offset, line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(s: &str) -> Atom {
+        Atom::Symbol(s.to_string())
+    }
+
+    fn position(input: &str) -> Position {
+        context_at(input, input.len()).position
+    }
+
+    fn word(input: &str) -> String {
+        context_at(input, input.len()).word.text
+    }
+
+    #[test]
+    fn test_complete_input_has_no_errors() {
+        let parse = parse_partial("(ls -la) (echo 'hi')");
+        assert!(parse.is_complete());
+        assert_eq!(parse.expressions.len(), 2);
+    }
+
+    #[test]
+    fn test_unclosed_lists_get_missing_close() {
+        let parse = parse_partial("(pipe (ls");
+        assert!(!parse.is_complete());
+
+        let PartialExpression::List(outer, span) = &parse.expressions[0] else {
+            panic!("expected list");
+        };
+        assert_eq!((span.start, span.end), (0, 9));
+        assert!(matches!(
+            outer.last(),
+            Some(PartialExpression::Error(SyntaxError::MissingClose, span)) if span.start == 9
+        ));
+
+        let PartialExpression::List(inner, _) = &outer[1] else {
+            panic!("expected inner list");
+        };
+        assert_eq!(
+            inner[0],
+            PartialExpression::Atom(symbol("ls"), Span::new(7, 9, 1, 8))
+        );
+        assert!(matches!(
+            inner[1],
+            PartialExpression::Error(SyntaxError::MissingClose, _)
+        ));
+    }
+
+    #[test]
+    fn test_unterminated_literal_is_an_error_node() {
+        let parse = parse_partial("(echo \"hi");
+        let PartialExpression::List(items, _) = &parse.expressions[0] else {
+            panic!("expected list");
+        };
+        assert_eq!(
+            items[1],
+            PartialExpression::Error(
+                SyntaxError::UnterminatedLiteral("hi".to_string()),
+                Span::new(6, 9, 1, 7)
+            )
+        );
+    }
+
+    #[test]
+    fn test_stray_close_is_kept_going_past() {
+        let parse = parse_partial("(ls)) (pwd)");
+        assert!(matches!(
+            parse.expressions[1],
+            PartialExpression::Error(SyntaxError::UnexpectedClose, _)
+        ));
+        assert!(matches!(parse.expressions[2], PartialExpression::List(..)));
+    }
+
+    #[test]
+    fn test_command_position() {
+        assert_eq!(position("("), Position::Command);
+        assert_eq!(position("(gi"), Position::Command);
+        assert_eq!(word("(gi"), "gi");
+        assert_eq!(position("(ls) (pipe (ls) (gr"), Position::Command);
+    }
+
+    #[test]
+    fn test_argument_position() {
+        assert_eq!(
+            position("(out (ls) fi"),
+            Position::Argument {
+                operator: Some("out".to_string()),
+                index: 2
+            }
+        );
+        assert_eq!(
+            position("(ls "),
+            Position::Argument {
+                operator: Some("ls".to_string()),
+                index: 1
+            }
+        );
+        assert_eq!(word("(ls "), "");
+    }
+
+    #[test]
+    fn test_closed_list_is_not_a_word() {
+        let context = context_at("(if (true)", 10);
+        assert_eq!(
+            context.position,
+            Position::Argument {
+                operator: Some("if".to_string()),
+                index: 2
+            }
+        );
+        assert_eq!(context.word.span, Span::new(10, 10, 1, 11));
+    }
+
+    #[test]
+    fn test_inside_literal() {
+        let context = context_at("(cat 'my fi", 11);
+        assert!(context.in_literal());
+        assert_eq!(context.word.text, "my fi");
+        assert_eq!(context.word.span.start, 5);
+        assert_eq!(
+            context.word.kind,
+            WordKind::Literal {
+                quote: '\'',
+                terminated: false
+            }
+        );
+
+        assert!(!context_at("(cat 'done'", 11).in_literal());
+    }
+
+    #[test]
+    fn test_inside_comment() {
+        assert_eq!(position("(ls) ; what"), Position::Comment);
+        assert_eq!(
+            position("(ls ; note\n"),
+            Position::Argument {
+                operator: Some("ls".to_string()),
+                index: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_top_level_and_cursor_mid_input() {
+        assert_eq!(position(""), Position::TopLevel);
+        assert_eq!(position("(ls) "), Position::TopLevel);
+
+        let context = context_at("(ls -la) (pwd)", 6);
+        assert_eq!(context.word.text, "-l");
+        assert_eq!(
+            context.position,
+            Position::Argument {
+                operator: Some("ls".to_string()),
+                index: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_open_escape_stays_with_word() {
+        let context = context_at("(cat my\\", 8);
+        assert_eq!(context.word.text, "my");
+        assert_eq!((context.word.span.start, context.word.span.end), (5, 8));
+    }
+}
@@ -0,0 +1,46 @@
+/// A region of the input.
+///
+/// `start` and `end` are byte offsets, `end` exclusive. `line` and
+/// `column` locate `start` for humans - both are 1-based, and
+/// columns count chars rather than bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// A span covering both `self` and `other`, which must
+    /// not start before `self`
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end: self.end.max(other.end),
+            ..self
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl Default for Span {
+    fn default() -> Self {
+        Span::new(0, 0, 1, 1)
+    }
+}
@@ -0,0 +1,233 @@
+//! Finding programs on `PATH`.
+//!
+//! Shared by the shell, which runs what a name resolves to, and the
+//! line editor, which completes names - so both agree on which of
+//! several same-named executables a command runs. The first
+//! directory on the search path holding one wins, and hides the
+//! rest.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+/// The directories searched for programs, in order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchPath {
+    dirs: Vec<PathBuf>,
+}
+
+/// Where a name resolves to, and what it hides
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub name: String,
+    /// What running the name runs
+    pub path: PathBuf,
+    /// Executables of the same name further along the search path,
+    /// which `path` shadows
+    pub shadowed: Vec<PathBuf>,
+}
+
+impl SearchPath {
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        SearchPath { dirs }
+    }
+
+    /// The search path in `PATH`
+    pub fn from_env() -> Self {
+        let path_var = std::env::var_os("PATH").unwrap_or_default();
+        SearchPath::new(std::env::split_paths(&path_var).collect())
+    }
+
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// The program `name` runs. Names with a `/` in them are paths
+    /// already and aren't searched for. The search stops at the
+    /// first directory holding one.
+    pub fn which(&self, name: &str) -> Option<PathBuf> {
+        if name.contains('/') {
+            return is_executable(Path::new(name)).then(|| PathBuf::from(name));
+        }
+        if name.is_empty() {
+            return None;
+        }
+
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(name))
+            .find(|path| is_executable(path))
+    }
+
+    /// Every executable called `name` on the search path, the one
+    /// that runs first
+    pub fn which_all(&self, name: &str) -> Vec<PathBuf> {
+        if name.is_empty() || name.contains('/') {
+            return vec![];
+        }
+
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(name))
+            .filter(|path| is_executable(path))
+            .collect()
+    }
+
+    /// Every executable on the search path, by name
+    pub fn executables(&self) -> Vec<Resolved> {
+        let listings: Vec<(&Path, Vec<String>)> = self
+            .dirs
+            .iter()
+            .map(|dir| (dir.as_path(), executables_in(dir)))
+            .collect();
+
+        resolve(listings.iter().map(|(dir, names)| (*dir, names.as_slice())))
+    }
+}
+
+/// Resolves the names listed for each directory, taking directories
+/// in search order. The result is sorted by name.
+pub fn resolve<'a>(listings: impl IntoIterator<Item = (&'a Path, &'a [String])>) -> Vec<Resolved> {
+    let mut resolved: BTreeMap<&str, Resolved> = BTreeMap::new();
+
+    for (dir, names) in listings {
+        for name in names {
+            let path = dir.join(name);
+            match resolved.get_mut(name.as_str()) {
+                Some(first) if first.path != path => first.shadowed.push(path),
+                Some(_) => {}
+                None => {
+                    resolved.insert(
+                        name,
+                        Resolved {
+                            name: name.clone(),
+                            path,
+                            shadowed: vec![],
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    resolved.into_values().collect()
+}
+
+/// Whether `path` is a file someone may execute, following symlinks
+pub fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+/// The names of the executable files in `dir`, sorted
+pub fn executables_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| is_executable(&entry.path()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::{TempDir, tempdir};
+
+    /// A fresh directory `name` inside `root`
+    fn subdir(root: &TempDir, name: &str) -> PathBuf {
+        let dir = root.path().join(name);
+        fs::create_dir(&dir).unwrap();
+        dir
+    }
+
+    fn executable(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_first_directory_wins() {
+        let root = tempdir().unwrap();
+        let (local, system) = (subdir(&root, "local"), subdir(&root, "system"));
+        let python = executable(&local, "python");
+        let shadowed = executable(&system, "python");
+        executable(&system, "ls");
+
+        let search = SearchPath::new(vec![local, system.clone()]);
+        assert_eq!(search.which("python"), Some(python.clone()));
+        assert_eq!(search.which_all("python"), vec![python, shadowed]);
+        assert_eq!(search.which("ls"), Some(system.join("ls")));
+        assert_eq!(search.which("nope"), None);
+    }
+
+    #[test]
+    fn test_only_executable_files_count() {
+        let root = tempdir().unwrap();
+        let dir = subdir(&root, "only");
+        fs::write(dir.join("notes"), "").unwrap();
+        fs::create_dir(dir.join("sub")).unwrap();
+
+        let search = SearchPath::new(vec![dir]);
+        assert_eq!(search.which("notes"), None);
+        assert_eq!(search.which("sub"), None);
+    }
+
+    #[test]
+    fn test_paths_are_not_searched() {
+        let root = tempdir().unwrap();
+        let dir = subdir(&root, "paths");
+        let tool = executable(&dir, "tool");
+        let search = SearchPath::new(vec![]);
+
+        assert_eq!(search.which(tool.to_str().unwrap()), Some(tool));
+        assert_eq!(search.which("./no/such/tool"), None);
+        assert_eq!(search.which(""), None);
+    }
+
+    #[test]
+    fn test_executables_report_shadowing() {
+        let root = tempdir().unwrap();
+        let (local, system) = (subdir(&root, "local"), subdir(&root, "system"));
+        executable(&local, "python");
+        executable(&system, "python");
+        executable(&system, "ls");
+
+        let search = SearchPath::new(vec![local.clone(), system.clone()]);
+        assert_eq!(
+            search.executables(),
+            vec![
+                Resolved {
+                    name: "ls".to_string(),
+                    path: system.join("ls"),
+                    shadowed: vec![],
+                },
+                Resolved {
+                    name: "python".to_string(),
+                    path: local.join("python"),
+                    shadowed: vec![system.join("python")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeated_directories_shadow_nothing() {
+        let root = tempdir().unwrap();
+        let dir = subdir(&root, "repeated");
+        executable(&dir, "ls");
+
+        let search = SearchPath::new(vec![dir.clone(), dir]);
+        assert!(search.executables()[0].shadowed.is_empty());
+    }
+}
@@ -15,6 +15,8 @@ struct TrieNodeSnapshot {
     edge: Option<char>,
     /// Original-cased strings that terminate exactly at this node.
     terminals: Vec<String>,
+    /// Weight of each terminal, in the same order.
+    weights: Vec<u32>,
     /// Total count of terminal strings in this subtrie.
     terminals_in_subtrie: usize,
     /// Children, sorted by edge char for deterministic output.
@@ -22,19 +24,18 @@ struct TrieNodeSnapshot {
 }
 
 fn snapshot_node(node: &TrieNode, edge: Option<char>) -> TrieNodeSnapshot {
-    let mut children: Vec<TrieNodeSnapshot> = node
+    // Children are kept ordered by edge char, so the JSON (and therefore
+    // the URL) is deterministic.
+    let children: Vec<TrieNodeSnapshot> = node
         .children
         .iter()
         .map(|(&c, child)| snapshot_node(child, Some(c)))
         .collect();
 
-    // Sort by edge char so the JSON (and therefore the URL) is deterministic
-    // regardless of HashMap iteration order.
-    children.sort_by_key(|n| n.edge);
-
     TrieNodeSnapshot {
         edge,
-        terminals: node.terminals.clone(),
+        terminals: node.terminals.iter().map(|t| t.name.clone()).collect(),
+        weights: node.terminals.iter().map(|t| t.weight).collect(),
         terminals_in_subtrie: node.terminals_in_subtrie,
         children,
     }